@@ -0,0 +1,103 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! # avr-context-derive: Derive macros for avr-context
+//!
+//! Do not use this crate directly.
+//! Enable the `derive` feature of the `avr-context` crate instead.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Fields, GenericArgument, PathArguments, Type, parse_macro_input,
+};
+
+/// Get the inner type `T` of a `MainCtxCell<T>` field type.
+///
+/// Returns `None`, if the field is not a `MainCtxCell`.
+fn main_ctx_cell_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "MainCtxCell" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+fn derive_main_ctx_access(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "MainCtxAccess can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "MainCtxAccess can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut accessors = Vec::new();
+    for field in &fields.named {
+        let Some(inner) = main_ctx_cell_inner(&field.ty) else {
+            continue;
+        };
+        let vis = &field.vis;
+        let name = field.ident.as_ref().expect("Named field without ident");
+        let setter = format_ident!("set_{}", name);
+        let get_doc = format!("Get a copy of the `{name}` cell data.");
+        let set_doc = format!("Set the `{name}` cell data.");
+        accessors.push(quote! {
+            #[doc = #get_doc]
+            #[inline(always)]
+            #vis fn #name(&self, m: &::avr_context::MainCtx<'_>) -> #inner {
+                self.#name.get(m)
+            }
+
+            #[doc = #set_doc]
+            #[inline(always)]
+            #vis fn #setter(&self, m: &::avr_context::MainCtx<'_>, value: #inner) {
+                self.#name.set(m, value)
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #(#accessors)*
+        }
+    })
+}
+
+/// Generate getters and setters for all `MainCtxCell` fields of a struct.
+///
+/// For each field `x: MainCtxCell<T>` the following methods are generated
+/// with the visibility of the field:
+///
+/// - `fn x(&self, m: &MainCtx<'_>) -> T`
+/// - `fn set_x(&self, m: &MainCtx<'_>, value: T)`
+///
+/// `T` must be `Copy`.
+/// All other fields are ignored.
+#[proc_macro_derive(MainCtxAccess)]
+pub fn main_ctx_access(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_main_ctx_access(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+// vim: ts=4 sw=4 expandtab
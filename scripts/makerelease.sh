@@ -20,7 +20,7 @@ hook_get_version()
 }
 
 project=avr-context
-conf_upload_packages="avr-context"
+conf_upload_packages="avr-context-derive avr-context"
 makerelease "$@"
 
 # vim: ts=4 sw=4 expandtab
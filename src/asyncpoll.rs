@@ -0,0 +1,115 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Polling of `async` futures in `main()` context.
+//!
+//! [poll_with_ctx] polls a future with a waker that carries a reference to the [MainCtx].
+//! Inside of the future, [main_ctx] gets the [MainCtx] back from the task context.
+//! That way futures can access [crate::MainCtxCell]s.
+
+use crate::context::MainCtx;
+use core::{
+    future::Future,
+    pin::Pin,
+    ptr::null,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+/// Waker vtable with a pointer to the [MainCtx] as data.
+static MAIN_CTX_VTABLE: RawWakerVTable = RawWakerVTable::new(clone_main_ctx, noop, noop, noop);
+
+/// Waker vtable without data.
+static NOOP_VTABLE: RawWakerVTable = RawWakerVTable::new(clone_noop, noop, noop, noop);
+
+fn clone_main_ctx(_: *const ()) -> RawWaker {
+    // Clones can outlive the `MainCtx`. Therefore, they must not carry it.
+    RawWaker::new(null(), &NOOP_VTABLE)
+}
+
+fn clone_noop(_: *const ()) -> RawWaker {
+    RawWaker::new(null(), &NOOP_VTABLE)
+}
+
+fn noop(_: *const ()) {}
+
+/// Poll the future `fut` once from `main()` context.
+///
+/// The future can get the [MainCtx] with [main_ctx].
+///
+/// Waking is a no-op.
+/// The main loop has to poll the future repeatedly until it is ready.
+#[inline(always)]
+pub fn poll_with_ctx<F: Future + ?Sized>(m: &MainCtx<'_>, fut: Pin<&mut F>) -> Poll<F::Output> {
+    let data = m as *const MainCtx<'_> as *const ();
+    // SAFETY:
+    // The vtable functions never dereference the data pointer.
+    // [main_ctx] dereferences it only while this waker is borrowed by the `Context`,
+    // which is shorter than the borrow of `m`.
+    // Clones of the waker don't carry the pointer.
+    let waker = unsafe { Waker::from_raw(RawWaker::new(data, &MAIN_CTX_VTABLE)) };
+    let mut cx = Context::from_waker(&waker);
+    fut.poll(&mut cx)
+}
+
+/// Get the [MainCtx] from the task context `cx` of a future.
+///
+/// Returns `None`, if the future is not polled by [poll_with_ctx].
+#[inline(always)]
+pub fn main_ctx<'a>(cx: &'a Context<'_>) -> Option<&'a MainCtx<'a>> {
+    let waker = cx.waker();
+    if !core::ptr::eq(waker.vtable(), &MAIN_CTX_VTABLE) {
+        return None;
+    }
+    // SAFETY:
+    // Only [poll_with_ctx] creates wakers with this vtable.
+    // Their data points to a `MainCtx` that outlives the waker.
+    Some(unsafe { &*(waker.data() as *const MainCtx<'a>) })
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use crate::MainCtxCell;
+    use core::pin::pin;
+
+    static VALUE: MainCtxCell<u16> = MainCtxCell::new(0);
+
+    struct ReadWhenSet;
+
+    impl Future for ReadWhenSet {
+        type Output = u16;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<u16> {
+            let m = main_ctx(cx).unwrap();
+            match VALUE.get(m) {
+                0 => Poll::Pending,
+                v => Poll::Ready(v),
+            }
+        }
+    }
+
+    #[test]
+    fn test_poll_with_ctx() {
+        let m = unsafe { MainCtx::new() };
+
+        let mut fut = pin!(ReadWhenSet);
+        assert_eq!(poll_with_ctx(&m, fut.as_mut()), Poll::Pending);
+        VALUE.set(&m, 42);
+        assert_eq!(poll_with_ctx(&m, fut.as_mut()), Poll::Ready(42));
+
+        let mut fut = pin!(async { VALUE.get(&m) + 1 });
+        assert_eq!(poll_with_ctx(&m, fut.as_mut()), Poll::Ready(43));
+
+        // Foreign and cloned wakers don't carry the `MainCtx`.
+        assert!(main_ctx(&Context::from_waker(Waker::noop())).is_none());
+        let mut fut = pin!(core::future::poll_fn(|cx| {
+            let waker = cx.waker().clone();
+            Poll::Ready(main_ctx(&Context::from_waker(&waker)).is_none())
+        }));
+        assert_eq!(poll_with_ctx(&m, fut.as_mut()), Poll::Ready(true));
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
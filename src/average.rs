@@ -0,0 +1,142 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Moving average of samples in interrupt context.
+
+use crate::{
+    Mutex,
+    cell::IrqCtxCell,
+    context::{IrqCtx, MainCtx},
+    with_cs,
+};
+use core::cell::Cell;
+
+/// Position and sum of the samples in the window.
+#[derive(Clone, Copy)]
+struct WindowState {
+    next: usize,
+    len: usize,
+    sum: u32,
+}
+
+/// Moving average over the last `W` samples.
+///
+/// The samples are pushed from interrupt context, e.g. from an ADC interrupt,
+/// with [Self::push].
+/// The latest average is published into a slot
+/// that the main loop reads with [Self::latest].
+///
+/// Each push only updates one sample slot and the running sum.
+/// Its run time does not depend on `W`.
+///
+/// `W` must be in the range `1..=65536`.
+pub struct MovingAverage<const W: usize> {
+    /// Sample window. Only accessed from interrupt context.
+    samples: Mutex<[Cell<u16>; W]>,
+    state: IrqCtxCell<WindowState>,
+    latest: Mutex<Cell<Option<u16>>>,
+}
+
+impl<const W: usize> MovingAverage<W> {
+    /// Create a new moving average without samples.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        const { assert!(W > 0 && W <= 0x1_0000) };
+        Self {
+            samples: Mutex::new([const { Cell::new(0) }; W]),
+            state: IrqCtxCell::new(WindowState {
+                next: 0,
+                len: 0,
+                sum: 0,
+            }),
+            latest: Mutex::new(Cell::new(None)),
+        }
+    }
+
+    /// Push a new `sample` from interrupt context.
+    ///
+    /// The oldest sample is dropped, if the window is full.
+    /// The average is calculated over the samples in the window,
+    /// which are less than `W` until the window has been filled once.
+    ///
+    /// Returns the new average.
+    #[inline(always)]
+    pub fn push(&self, irq: &IrqCtx<'_>, sample: u16) -> u16 {
+        let mut state = self.state.get(irq);
+        let slot = &self.samples.borrow(irq.cs())[state.next];
+        let oldest = slot.replace(sample);
+        if state.len == W {
+            state.sum -= oldest as u32;
+        } else {
+            state.len += 1;
+        }
+        state.sum += sample as u32;
+        state.next = (state.next + 1) % W;
+        let average = (state.sum / state.len as u32) as u16;
+        self.state.set(irq, state);
+        self.latest.borrow(irq.cs()).set(Some(average));
+        average
+    }
+
+    /// Get the latest average from `main()` context.
+    ///
+    /// Returns `None`, if no sample has been pushed, yet.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn latest(&self, _: &MainCtx<'_>) -> Option<u16> {
+        with_cs(|cs| self.latest.borrow(cs).get())
+    }
+}
+
+impl<const W: usize> Default for MovingAverage<W> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_moving_average() {
+        let m = unsafe { MainCtx::new() };
+
+        let avg: MovingAverage<4> = MovingAverage::new();
+        assert_eq!(avg.latest(&m), None);
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert_eq!(avg.push(&irq, 10), 10);
+            assert_eq!(avg.push(&irq, 20), 15);
+            assert_eq!(avg.push(&irq, 30), 20);
+            assert_eq!(avg.push(&irq, 40), 25);
+            // The window is full. 10 is dropped.
+            assert_eq!(avg.push(&irq, 50), 35);
+        }
+        assert_eq!(avg.latest(&m), Some(35));
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            for _ in 0..4 {
+                avg.push(&irq, u16::MAX);
+            }
+        }
+        assert_eq!(avg.latest(&m), Some(u16::MAX));
+    }
+
+    #[test]
+    fn test_moving_average_single() {
+        let irq = unsafe { IrqCtx::new() };
+
+        let avg: MovingAverage<1> = MovingAverage::new();
+        assert_eq!(avg.push(&irq, 7), 7);
+        assert_eq!(avg.push(&irq, 3), 3);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,145 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Memory barriers for custom data exchange between `main()` and interrupt context.
+//!
+//! The cell and queue types of this crate already contain all barriers they need.
+//! This module is only needed, if you write your own shared memory protocol
+//! between `main()` context and interrupt context.
+//!
+//! # Which barrier to use
+//!
+//! AVR is a single core architecture without caches and without out-of-order memory access.
+//! The hardware never reorders memory accesses.
+//! Only the compiler does.
+//! Between `main()` context and interrupt context on the same core
+//! a [compiler] barrier is therefore sufficient.
+//! It does not generate any instruction.
+//!
+//! [full] is a hardware memory fence.
+//! It is needed, if the data is exchanged with an observer that is not running on the same core,
+//! for example a DMA engine or a debugger.
+//! It is also the correct choice in host tests, where `main()` and the "interrupt"
+//! may run on different threads.
+//! On AVR it also does not generate any instruction,
+//! but it documents the intent and keeps the code correct, if it is ever ported.
+//!
+//! Note that critical sections entered with [with_cs](crate::with_cs)
+//! disable interrupts and already act as barriers.
+//! You don't need additional barriers for data that is only accessed within critical sections.
+//!
+//! [MainCtx::batch](crate::MainCtx::batch) is not a critical section.
+//! It does not disable interrupts.
+//! It only places fences before and after the batch,
+//! so that the batched accesses are not moved across its boundaries.
+//! Don't add explicit barriers around a batch. They would be redundant:
+//!
+//! ```
+//! use avr_context::{MainCtx, MainCtxCell};
+//!
+//! static SETPOINT: MainCtxCell<u16> = MainCtxCell::new(0);
+//! static LIMIT: MainCtxCell<u16> = MainCtxCell::new(0);
+//!
+//! fn update(m: &MainCtx<'_>, setpoint: u16, limit: u16) {
+//!     m.batch(|m| {
+//!         SETPOINT.set(m, setpoint);
+//!         LIMIT.set(m, limit);
+//!     });
+//!     m.sleep(|| {
+//!         // Enter the sleep mode.
+//!     });
+//! }
+//! ```
+//!
+//! # Example: producer/consumer flag
+//!
+//! The interrupt produces a value and then raises a flag.
+//! `main()` polls the flag and then consumes the value.
+//! The [Ordering::Release] store and the [Ordering::Acquire] load of the flag
+//! order the accesses to the value.
+//! The [compiler] barriers make that ordering explicit for the `Relaxed` data accesses.
+//!
+//! ```
+//! use avr_context::barrier::{self, Ordering};
+//! use core::sync::atomic::{AtomicBool, AtomicU8};
+//!
+//! static VALUE: AtomicU8 = AtomicU8::new(0);
+//! static READY: AtomicBool = AtomicBool::new(false);
+//!
+//! /// Called from interrupt context.
+//! fn produce(value: u8) {
+//!     VALUE.store(value, Ordering::Relaxed);
+//!     // The value must be written before the flag is raised.
+//!     barrier::compiler();
+//!     READY.store(true, Ordering::Release);
+//! }
+//!
+//! /// Called from `main()` context.
+//! fn consume() -> Option<u8> {
+//!     if READY.swap(false, Ordering::Acquire) {
+//!         // The value must not be read before the flag has been seen.
+//!         barrier::compiler();
+//!         Some(VALUE.load(Ordering::Relaxed))
+//!     } else {
+//!         None
+//!     }
+//! }
+//!
+//! assert_eq!(consume(), None);
+//! produce(42);
+//! assert_eq!(consume(), Some(42));
+//! assert_eq!(consume(), None);
+//! ```
+//!
+//! # Example: hand over to an external observer
+//!
+//! Data that is read by a DMA engine or a debugger needs a [full] barrier
+//! before the observer is notified.
+//!
+//! ```
+//! use avr_context::barrier::{self, Ordering};
+//! use core::sync::atomic::{AtomicBool, AtomicU16};
+//!
+//! static SAMPLE: AtomicU16 = AtomicU16::new(0);
+//! static SAMPLE_VALID: AtomicBool = AtomicBool::new(false);
+//!
+//! fn publish(sample: u16) {
+//!     SAMPLE_VALID.store(false, Ordering::Relaxed);
+//!     barrier::full();
+//!     SAMPLE.store(sample, Ordering::Relaxed);
+//!     barrier::full();
+//!     SAMPLE_VALID.store(true, Ordering::Relaxed);
+//! }
+//!
+//! publish(1234);
+//! assert!(SAMPLE_VALID.load(Ordering::Relaxed));
+//! assert_eq!(SAMPLE.load(Ordering::Relaxed), 1234);
+//! ```
+
+use core::sync::atomic::{compiler_fence, fence};
+
+pub use core::sync::atomic::Ordering;
+
+/// Full memory barrier.
+///
+/// This is a `SeqCst` [fence].
+/// Use it for data that is observed by something outside of the current core.
+/// See the [module documentation](self) for details.
+#[inline(always)]
+pub fn full() {
+    fence(Ordering::SeqCst);
+}
+
+/// Compiler barrier.
+///
+/// This is a `SeqCst` [compiler_fence].
+/// It prevents the compiler from reordering memory accesses across the barrier.
+/// This is sufficient for data exchange between `main()` context and interrupt context.
+/// See the [module documentation](self) for details.
+#[inline(always)]
+pub fn compiler() {
+    compiler_fence(Ordering::SeqCst);
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,171 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Heap allocated cells.
+//!
+//! This module is only available with the `alloc` feature.
+
+use crate::context::MainCtx;
+use alloc::boxed::Box;
+use core::cell::Cell;
+
+/// A heap allocated cell that can only be accessed from `main()` context.
+///
+/// This is the boxed counterpart of [crate::MainCtxCell].
+/// It is useful for large state or state with a size that is only known at runtime,
+/// such as slices.
+///
+/// There is no way to access `T` from interrupt context.
+/// Therefore, all allowed accesses to `T` (from main context)
+/// do not need to disable interrupts or take any other measures
+/// against interruption.
+pub struct MainCtxBox<T: ?Sized> {
+    inner: Box<Cell<T>>,
+}
+
+impl<T> MainCtxBox<T> {
+    /// Allocate a new `MainCtxBox` with the given initial value.
+    #[inline(always)]
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner: Box::new(Cell::new(inner)),
+        }
+    }
+
+    /// Replace the inner value with `inner` and return the old value.
+    #[inline(always)]
+    pub fn replace(&self, _: &MainCtx<'_>, inner: T) -> T {
+        self.inner.replace(inner)
+    }
+
+    /// Consume the box and return the inner value.
+    ///
+    /// No context marker is needed, because ownership of the box guarantees exclusive access.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+impl<T: ?Sized> MainCtxBox<T> {
+    /// Create a new `MainCtxBox` from an already allocated `Box`.
+    #[inline(always)]
+    pub fn from_box(inner: Box<T>) -> Self {
+        let ptr = Box::into_raw(inner) as *mut Cell<T>;
+        // SAFETY:
+        // `Cell<T>` has the same memory layout as `T`.
+        // The pointer has been allocated by `Box` with the layout of `T`.
+        let inner = unsafe { Box::from_raw(ptr) };
+        Self { inner }
+    }
+
+    /// Get a mutable reference to the inner value.
+    ///
+    /// No context marker is needed, because the `&mut self` borrow guarantees exclusive access.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+}
+
+impl<T: Copy> MainCtxBox<T> {
+    /// Get a copy of the inner data from a main context `MainCtx`.
+    #[inline(always)]
+    pub fn get(&self, _: &MainCtx<'_>) -> T {
+        self.inner.get()
+    }
+
+    /// Set the inner data from a main context `MainCtx`.
+    #[inline(always)]
+    pub fn set(&self, _: &MainCtx<'_>, inner: T) {
+        self.inner.set(inner);
+    }
+}
+
+impl<T: Copy> MainCtxBox<[T]> {
+    /// Get the number of slice elements.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.inner.as_slice_of_cells().len()
+    }
+
+    /// Returns `true`, if the slice has no elements.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get a copy of the slice element at `index` from a main context `MainCtx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `index` is out of bounds.
+    #[inline(always)]
+    pub fn get_at(&self, _: &MainCtx<'_>, index: usize) -> T {
+        self.inner.as_slice_of_cells()[index].get()
+    }
+
+    /// Set the slice element at `index` from a main context `MainCtx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `index` is out of bounds.
+    #[inline(always)]
+    pub fn set_at(&self, _: &MainCtx<'_>, index: usize, inner: T) {
+        self.inner.as_slice_of_cells()[index].set(inner);
+    }
+}
+
+impl<T: Default> Default for MainCtxBox<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+// SAFETY: The inner data can only be accessed from `main()` context.
+// This is the same as `Mutex<Cell<T>>` in [crate::MainCtxCell].
+unsafe impl<T: ?Sized + Send> Sync for MainCtxBox<T> {}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_main_ctx_box() {
+        let m = unsafe { MainCtx::new() };
+
+        let a: MainCtxBox<[u16; 64]> = MainCtxBox::new([1; 64]);
+        assert_eq!(a.get(&m), [1; 64]);
+        a.set(&m, [2; 64]);
+        assert_eq!(a.replace(&m, [3; 64]), [2; 64]);
+
+        let mut a = a;
+        a.get_mut()[63] = 4;
+        let inner = a.into_inner();
+        assert_eq!(inner[62], 3);
+        assert_eq!(inner[63], 4);
+    }
+
+    #[test]
+    fn test_main_ctx_box_slice() {
+        let m = unsafe { MainCtx::new() };
+
+        let len = 5;
+        let mut a: MainCtxBox<[u8]> = MainCtxBox::from_box(vec![0; len].into_boxed_slice());
+        assert_eq!(a.len(), 5);
+        assert!(!a.is_empty());
+        a.set_at(&m, 4, 42);
+        assert_eq!(a.get_at(&m, 4), 42);
+        a.get_mut()[0] = 1;
+        assert_eq!(a.get_at(&m, 0), 1);
+
+        let b: MainCtxBox<[u8]> = MainCtxBox::from_box(vec![].into_boxed_slice());
+        assert!(b.is_empty());
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,133 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Main context buffer types.
+
+use crate::{Mutex, context::MainCtx, ring::Ring};
+
+/// A FIFO queue that can only be accessed from `main()` context.
+///
+/// Both ends of the queue are in the main context.
+/// Therefore, no atomics and no critical sections are needed.
+///
+/// The queue can hold up to `N` elements.
+pub struct MainCtxBuffer<T, const N: usize> {
+    ring: Mutex<Ring<T, N>>,
+}
+
+impl<T, const N: usize> MainCtxBuffer<T, N> {
+    /// Create a new empty `MainCtxBuffer`.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            ring: Mutex::new(Ring::new()),
+        }
+    }
+
+    #[inline(always)]
+    fn ring<'a>(&'a self, m: &MainCtx<'a>) -> &'a Ring<T, N> {
+        // SAFETY: We only use the cs for the main context, where it is allowed to be used.
+        self.ring.borrow(unsafe { m.cs() })
+    }
+
+    /// Append `inner` to the end of the queue.
+    ///
+    /// Returns `inner` back, if the queue is full.
+    #[inline(always)]
+    pub fn push(&self, m: &MainCtx<'_>, inner: T) -> Result<(), T> {
+        self.ring(m).push(inner)
+    }
+
+    /// Remove the first element from the queue.
+    #[inline(always)]
+    pub fn pop(&self, m: &MainCtx<'_>) -> Option<T> {
+        self.ring(m).pop()
+    }
+
+    /// Get the number of elements in the queue.
+    #[inline(always)]
+    pub fn len(&self, m: &MainCtx<'_>) -> usize {
+        self.ring(m).len()
+    }
+
+    /// Returns `true` if the queue is empty.
+    #[inline(always)]
+    pub fn is_empty(&self, m: &MainCtx<'_>) -> bool {
+        self.len(m) == 0
+    }
+
+    /// Returns `true` if the queue is full.
+    #[inline(always)]
+    pub fn is_full(&self, m: &MainCtx<'_>) -> bool {
+        self.len(m) >= N
+    }
+
+    /// Get the maximum number of elements in the queue.
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<T, const N: usize> Default for MainCtxBuffer<T, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_main_ctx_buffer() {
+        let m = unsafe { MainCtx::new() };
+
+        let buf: MainCtxBuffer<u16, 3> = MainCtxBuffer::new();
+        assert_eq!(buf.capacity(), 3);
+        assert!(buf.is_empty(&m));
+        assert_eq!(buf.pop(&m), None);
+
+        assert_eq!(buf.push(&m, 1), Ok(()));
+        assert_eq!(buf.push(&m, 2), Ok(()));
+        assert_eq!(buf.push(&m, 3), Ok(()));
+        assert!(buf.is_full(&m));
+        assert_eq!(buf.push(&m, 4), Err(4));
+        assert_eq!(buf.len(&m), 3);
+
+        assert_eq!(buf.pop(&m), Some(1));
+        assert_eq!(buf.push(&m, 5), Ok(()));
+        assert_eq!(buf.pop(&m), Some(2));
+        assert_eq!(buf.pop(&m), Some(3));
+        assert_eq!(buf.pop(&m), Some(5));
+        assert_eq!(buf.pop(&m), None);
+        assert!(buf.is_empty(&m));
+
+        let empty: MainCtxBuffer<u16, 0> = MainCtxBuffer::new();
+        assert!(empty.is_full(&m));
+        assert_eq!(empty.push(&m, 1), Err(1));
+        assert_eq!(empty.pop(&m), None);
+    }
+
+    #[test]
+    fn test_main_ctx_buffer_drop() {
+        use std::rc::Rc;
+
+        let m = unsafe { MainCtx::new() };
+
+        let rc = Rc::new(());
+        let buf: MainCtxBuffer<Rc<()>, 2> = MainCtxBuffer::new();
+        buf.push(&m, Rc::clone(&rc)).unwrap();
+        buf.push(&m, Rc::clone(&rc)).unwrap();
+        assert_eq!(Rc::strong_count(&rc), 3);
+        drop(buf.pop(&m));
+        assert_eq!(Rc::strong_count(&rc), 2);
+        drop(buf);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
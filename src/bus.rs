@@ -0,0 +1,137 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Publish/subscribe event bus in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// A subscriber function that runs in `main()` context.
+pub type Subscriber<E> = fn(&MainCtx<'_>, E);
+
+/// Event bus with up to `S` subscribers that can only be used from `main()` context.
+///
+/// Modules subscribe to the bus with [Self::subscribe].
+/// [Self::publish] calls all subscribers with the event.
+/// This decouples the publisher of an event from its consumers.
+pub struct EventBus<E: Copy, const S: usize> {
+    subscribers: [MainCtxCell<Option<Subscriber<E>>>; S],
+}
+
+impl<E: Copy, const S: usize> EventBus<E, S> {
+    /// Create a new event bus without subscribers.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            subscribers: [const { MainCtxCell::new(None) }; S],
+        }
+    }
+
+    /// Add the `subscriber` to the bus.
+    ///
+    /// Returns the subscriber id that can be used for [Self::unsubscribe].
+    /// Returns the `subscriber` back, if all `S` subscriber slots are in use.
+    #[inline(always)]
+    pub fn subscribe(
+        &self,
+        m: &MainCtx<'_>,
+        subscriber: Subscriber<E>,
+    ) -> Result<usize, Subscriber<E>> {
+        match self.subscribers.iter().position(|s| s.get(m).is_none()) {
+            Some(id) => {
+                self.subscribers[id].set(m, Some(subscriber));
+                Ok(id)
+            }
+            None => Err(subscriber),
+        }
+    }
+
+    /// Remove the subscriber `id` from the bus.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is not smaller than `S`.
+    #[inline(always)]
+    pub fn unsubscribe(&self, m: &MainCtx<'_>, id: usize) {
+        self.subscribers[id].set(m, None);
+    }
+
+    /// Call all subscribers with the `event`.
+    ///
+    /// The subscribers are called in the order of their ids.
+    /// Subscribers may publish further events.
+    ///
+    /// Returns the number of called subscribers.
+    #[inline(always)]
+    pub fn publish(&self, m: &MainCtx<'_>, event: E) -> usize {
+        let mut count = 0;
+        for subscriber in &self.subscribers {
+            if let Some(subscriber) = subscriber.get(m) {
+                subscriber(m, event);
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+impl<E: Copy, const S: usize> Default for EventBus<E, S> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    enum Event {
+        Button(u8),
+        Tick,
+    }
+
+    static BUTTONS: MainCtxCell<u32> = MainCtxCell::new(0);
+    static TICKS: MainCtxCell<u32> = MainCtxCell::new(0);
+
+    fn on_button(m: &MainCtx<'_>, event: Event) {
+        if let Event::Button(b) = event {
+            BUTTONS.set(m, BUTTONS.get(m) + u32::from(b));
+        }
+    }
+
+    fn on_tick(m: &MainCtx<'_>, event: Event) {
+        if let Event::Tick = event {
+            TICKS.set(m, TICKS.get(m) + 1);
+        }
+    }
+
+    #[test]
+    fn test_event_bus() {
+        let m = unsafe { MainCtx::new() };
+
+        let bus: EventBus<Event, 3> = EventBus::new();
+        assert_eq!(bus.publish(&m, Event::Tick), 0);
+
+        assert_eq!(bus.subscribe(&m, on_button), Ok(0));
+        assert_eq!(bus.subscribe(&m, on_tick), Ok(1));
+        assert_eq!(bus.subscribe(&m, on_button), Ok(2));
+        assert!(bus.subscribe(&m, on_tick).is_err());
+
+        assert_eq!(bus.publish(&m, Event::Button(5)), 3);
+        assert_eq!(bus.publish(&m, Event::Tick), 3);
+        assert_eq!(BUTTONS.get(&m), 10);
+        assert_eq!(TICKS.get(&m), 1);
+
+        bus.unsubscribe(&m, 0);
+        assert_eq!(bus.publish(&m, Event::Button(1)), 2);
+        assert_eq!(BUTTONS.get(&m), 11);
+        assert_eq!(bus.subscribe(&m, on_tick), Ok(0));
+        assert_eq!(bus.publish(&m, Event::Tick), 3);
+        assert_eq!(TICKS.get(&m), 3);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,89 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Memoization of computed values in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// Cache for a value that is computed from a rarely changing input.
+///
+/// The cache stores the last input and the corresponding output.
+/// The output is only recomputed, if the input differs from the stored input.
+///
+/// The cache can only be accessed from `main()` context.
+pub struct Cached<In, Out> {
+    inner: MainCtxCell<Option<(In, Out)>>,
+}
+
+impl<In, Out> Cached<In, Out> {
+    /// Create a new empty cache.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            inner: MainCtxCell::new(None),
+        }
+    }
+}
+
+impl<In: Copy + PartialEq, Out: Copy> Cached<In, Out> {
+    /// Get the output for `input`.
+    ///
+    /// `compute` is only called, if the cache is empty
+    /// or if `input` differs from the input of the cached output.
+    #[inline(always)]
+    pub fn get(&self, m: &MainCtx<'_>, input: In, compute: impl FnOnce(&In) -> Out) -> Out {
+        if let Some((cached_input, output)) = self.inner.get(m)
+            && cached_input == input
+        {
+            return output;
+        }
+        let output = compute(&input);
+        self.inner.set(m, Some((input, output)));
+        output
+    }
+
+    /// Clear the cache.
+    ///
+    /// The next call to [Self::get] will call `compute`.
+    #[inline(always)]
+    pub fn invalidate(&self, m: &MainCtx<'_>) {
+        self.inner.set(m, None);
+    }
+}
+
+impl<In, Out> Default for Cached<In, Out> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cached() {
+        let m = unsafe { MainCtx::new() };
+
+        let cache: Cached<u8, u16> = Cached::new();
+        let mut count = 0;
+        let mut square = |input: &u8| {
+            count += 1;
+            *input as u16 * *input as u16
+        };
+
+        assert_eq!(cache.get(&m, 3, &mut square), 9);
+        assert_eq!(cache.get(&m, 3, &mut square), 9);
+        assert_eq!(cache.get(&m, 4, &mut square), 16);
+        assert_eq!(cache.get(&m, 4, &mut square), 16);
+        assert_eq!(cache.get(&m, 3, &mut square), 9);
+        cache.invalidate(&m);
+        assert_eq!(cache.get(&m, 3, &mut square), 9);
+        assert_eq!(count, 4);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,226 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Event capturing from interrupt context for timing analysis.
+
+use crate::{
+    Mutex,
+    context::{IrqCtx, MainCtx},
+    ring::Ring,
+    time::Clock,
+    with_cs,
+};
+use core::cell::Cell;
+
+/// A fixed size ring of the most recent `N` events captured in interrupt context
+/// and read from `main()` context.
+///
+/// This works like a logic analyzer:
+/// The interrupt captures events (e.g. event codes or timestamps)
+/// and the main loop reads them later, e.g. to dump them over UART.
+///
+/// The events are `u16` by default. Any `E: Copy` can be captured.
+///
+/// If the ring is full, capturing a new event overwrites the oldest event.
+pub struct CaptureRing<const N: usize, E = u16> {
+    ring: Mutex<Ring<E, N>>,
+    lost: Mutex<Cell<u16>>,
+}
+
+impl<const N: usize, E: Copy> CaptureRing<N, E> {
+    /// Create a new empty capture ring.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            ring: Mutex::new(Ring::new()),
+            lost: Mutex::new(Cell::new(0)),
+        }
+    }
+
+    /// Capture an `event` from interrupt context.
+    ///
+    /// The oldest event is overwritten, if the ring is full.
+    #[inline(always)]
+    pub fn capture(&self, irq: &IrqCtx<'_>, event: E) {
+        if self.ring.borrow(irq.cs()).push_overwrite(event).is_some() {
+            let lost = self.lost.borrow(irq.cs());
+            lost.set(lost.get().saturating_add(1));
+        }
+    }
+
+    /// Get the number of events that have been overwritten since the last [Self::read_all].
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn lost(&self, _: &MainCtx<'_>) -> u16 {
+        with_cs(|cs| self.lost.borrow(cs).get())
+    }
+
+    /// Remove all captured events and pass them to `f` in capture order.
+    ///
+    /// Each event is removed in a short critical section.
+    /// `f` is called with interrupts enabled.
+    /// At most the number of events that are present at the start of the call
+    /// are passed to `f`.
+    ///
+    /// Returns the number of events that have been overwritten since the last call.
+    #[inline(always)]
+    pub fn read_all(&self, _: &MainCtx<'_>, mut f: impl FnMut(E)) -> u16 {
+        let (len, lost) =
+            with_cs(|cs| (self.ring.borrow(cs).len(), self.lost.borrow(cs).replace(0)));
+        for _ in 0..len {
+            if let Some(event) = with_cs(|cs| self.ring.borrow(cs).pop()) {
+                f(event);
+            }
+        }
+        lost
+    }
+
+    /// Get the maximum number of events in the ring.
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize, E: Copy> Default for CaptureRing<N, E> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An event with the time of its occurrence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimedEvent {
+    /// Clock value at the time of the event.
+    pub timestamp: u32,
+    /// Event code.
+    pub code: u16,
+}
+
+/// A [CaptureRing] of timestamped events.
+///
+/// Overwritten events are counted, so that the main loop knows that the log is incomplete.
+pub type EventLog<const N: usize> = CaptureRing<N, TimedEvent>;
+
+impl<const N: usize> CaptureRing<N, TimedEvent> {
+    /// Log the event `code` with the current time of `clock` from interrupt context.
+    ///
+    /// The oldest event is overwritten, if the ring is full.
+    #[inline(always)]
+    pub fn log(&self, irq: &IrqCtx<'_>, code: u16, clock: &impl Clock) {
+        let timestamp = irq.timestamp(clock);
+        self.capture(irq, TimedEvent { timestamp, code });
+    }
+
+    /// Returns `true`, if events have been overwritten since the last [Self::read_all].
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn overflowed(&self, m: &MainCtx<'_>) -> bool {
+        self.lost(m) > 0
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use crate::time::test::MockClock;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_capture_ring() {
+        let m = unsafe { MainCtx::new() };
+
+        let ring: CaptureRing<3> = CaptureRing::new();
+        assert_eq!(ring.capacity(), 3);
+
+        let mut events = Vec::new();
+        assert_eq!(ring.read_all(&m, |e| events.push(e)), 0);
+        assert!(events.is_empty());
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            ring.capture(&irq, 10);
+            ring.capture(&irq, 11);
+        }
+        assert_eq!(ring.read_all(&m, |e| events.push(e)), 0);
+        assert_eq!(events, [10, 11]);
+
+        events.clear();
+        {
+            let irq = unsafe { IrqCtx::new() };
+            for event in 20..25 {
+                ring.capture(&irq, event);
+            }
+        }
+        assert_eq!(ring.read_all(&m, |e| events.push(e)), 2);
+        assert_eq!(events, [22, 23, 24]);
+
+        events.clear();
+        assert_eq!(ring.read_all(&m, |e| events.push(e)), 0);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_event_log() {
+        let m = unsafe { MainCtx::new() };
+
+        let clock = MockClock(Cell::new(100));
+        let log: EventLog<3> = EventLog::new();
+        assert_eq!(log.capacity(), 3);
+        assert!(!log.overflowed(&m));
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            log.log(&irq, 1, &clock);
+            clock.0.set(150);
+            log.log(&irq, 2, &clock);
+        }
+        assert!(!log.overflowed(&m));
+
+        let mut events = Vec::new();
+        assert_eq!(log.read_all(&m, |e| events.push(e)), 0);
+        assert_eq!(
+            events,
+            [
+                TimedEvent {
+                    timestamp: 100,
+                    code: 1
+                },
+                TimedEvent {
+                    timestamp: 150,
+                    code: 2
+                },
+            ]
+        );
+
+        events.clear();
+        {
+            let irq = unsafe { IrqCtx::new() };
+            for code in 10..15 {
+                clock.0.set(u32::from(code) * 10);
+                log.log(&irq, code, &clock);
+            }
+        }
+        assert!(log.overflowed(&m));
+        assert_eq!(log.read_all(&m, |e| events.push(e)), 2);
+        assert!(!log.overflowed(&m));
+        assert_eq!(
+            events
+                .iter()
+                .map(|e| (e.timestamp, e.code))
+                .collect::<Vec<_>>(),
+            [(120, 12), (130, 13), (140, 14)]
+        );
+
+        events.clear();
+        assert_eq!(log.read_all(&m, |e| events.push(e)), 0);
+        assert!(events.is_empty());
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
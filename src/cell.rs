@@ -6,14 +6,110 @@
 
 use crate::{
     CriticalSection, Mutex,
-    context::{InitCtx, IrqCtx, MainCtx},
+    context::{InitCtx, IrqCtx, MainCtx, TaggedIrqCtx},
 };
 use core::{
     cell::{Cell, UnsafeCell},
+    hash::Hash,
+    marker::PhantomData,
     mem::{MaybeUninit, transmute_copy},
+    num::Wrapping,
+    ops::Add,
 };
 
+/// Types that have a zero value, which is available in `const` context.
+///
+/// The zero value is `0` for numbers, `false` for `bool`, `'\0'` for `char`
+/// and `None` for `Option`. Arrays are zero, if all elements are zero.
+/// This is the logical zero value. It is not necessarily an all-zero bit pattern.
+///
+/// This is used for zeroing cells, e.g. buffers between frames.
+/// See [MainCtxCell::zeroed] and [MainCtxCell::zero].
+pub trait ZeroValue: Copy {
+    /// The zero value.
+    const ZERO: Self;
+}
+
+macro_rules! impl_zero_value {
+    ($($ty:ty = $zero:expr),* $(,)?) => {
+        $(
+            impl ZeroValue for $ty {
+                const ZERO: Self = $zero;
+            }
+        )*
+    };
+}
+
+impl_zero_value!(
+    u8 = 0,
+    u16 = 0,
+    u32 = 0,
+    u64 = 0,
+    u128 = 0,
+    usize = 0,
+    i8 = 0,
+    i16 = 0,
+    i32 = 0,
+    i64 = 0,
+    i128 = 0,
+    isize = 0,
+    f32 = 0.0,
+    f64 = 0.0,
+    bool = false,
+    char = '\0',
+);
+
+impl<T: ZeroValue, const N: usize> ZeroValue for [T; N] {
+    const ZERO: Self = [T::ZERO; N];
+}
+
+impl<T: Copy> ZeroValue for Option<T> {
+    const ZERO: Self = None;
+}
+
+/// Record a [MainCtxCell] access with the `access-trace` feature.
+macro_rules! trace_access {
+    ($cell:expr, $op:ident) => {
+        #[cfg(all(feature = "access-trace", not(target_arch = "avr")))]
+        $crate::trace::record($cell, $crate::trace::AccessOp::$op);
+    };
+}
+
 /// Lazy initialization of static variables.
+///
+/// # Thread safety
+///
+/// `InitCtxCell<T>` is `Sync` if `T` is `Send`.
+/// This is the same bound as for `critical_section::Mutex`.
+///
+/// All references to the inner data are bound to a `CriticalSection`:
+/// The initialization context and interrupt context run with interrupts disabled
+/// and all other accesses need a `CriticalSection` argument.
+/// On single core AVR, two critical sections can never overlap in time.
+/// Therefore, references to the inner data that have been obtained
+/// from different contexts can never be used concurrently.
+/// Not even if `T` has interior mutability.
+/// `T: Sync` is not required.
+///
+/// `T` must be `Send`, because the inner data is
+/// accessed from both `main()` context and interrupt context.
+///
+/// ```compile_fail
+/// use avr_context::InitCtxCell;
+/// use std::rc::Rc;
+///
+/// // This does not compile, because `Rc` is not `Send`.
+/// static CELL: InitCtxCell<Rc<u8>> = unsafe { InitCtxCell::uninit() };
+/// ```
+///
+/// `Send` types with interior mutability, that are not `Sync`, can be used:
+///
+/// ```
+/// use avr_context::InitCtxCell;
+/// use core::cell::Cell;
+///
+/// static CELL: InitCtxCell<Cell<u8>> = unsafe { InitCtxCell::uninit() };
+/// ```
 #[repr(transparent)]
 pub struct InitCtxCell<T>(UnsafeCell<MaybeUninit<T>>);
 
@@ -61,6 +157,46 @@ impl<T> InitCtxCell<T> {
         unsafe { (*self.0.get()).assume_init_ref() }
     }
 
+    /// Re-initialize the already initialized cell with new `inner` data
+    /// and return a reference to it.
+    ///
+    /// The previous inner value is dropped.
+    ///
+    /// On AVR the initialization runs only once per boot.
+    /// This is useful for host test harnesses that run [MainCtx::new_with_init]
+    /// multiple times for repeated setup and teardown.
+    ///
+    /// # Safety
+    ///
+    /// The cell must already have been initialized with [Self::init].
+    ///
+    /// No reference to the previous inner value must be alive.
+    #[inline(always)]
+    pub unsafe fn reinit<'ctx>(&self, c: &'ctx InitCtx, inner: T) -> &'ctx T {
+        // SAFETY:
+        // Our caller guarantees that the previous inner value is initialized
+        // and that there are no references to it.
+        // The `InitCtx` argument ensures that there are no concurrent accesses.
+        unsafe { (*self.0.get()).assume_init_drop() };
+        self.init(c, inner)
+    }
+
+    /// Initialize the cell with data computed from the already initialized `src` cell.
+    ///
+    /// `src` must already have been initialized with [Self::init].
+    ///
+    /// This must be called *once* during construction of the [MainCtx] to initialize the cell.
+    #[inline(always)]
+    pub fn init_from<'ctx, U>(
+        &self,
+        c: &'ctx InitCtx,
+        src: &InitCtxCell<U>,
+        f: impl FnOnce(&U) -> T,
+    ) -> &'ctx T {
+        let inner = f(src.as_ref_with_initctx(c));
+        self.init(c, inner)
+    }
+
     /// Get a reference to the inner data with the given critical section.
     #[inline(always)]
     pub fn as_ref_with_cs<'cs>(&self, _: CriticalSection<'cs>) -> &'cs T {
@@ -81,6 +217,53 @@ impl<T> InitCtxCell<T> {
     pub fn as_ref_with_irqctx<'ctx>(&self, c: &'ctx IrqCtx) -> &'ctx T {
         self.as_ref_with_cs(c.cs())
     }
+
+    /// Get a reference to the inner data from a main context `MainCtx`.
+    ///
+    /// `T` must be `Sync`, because interrupt context can access the inner data
+    /// at the same time via [Self::as_ref_with_irqctx].
+    #[inline(always)]
+    pub fn as_ref_with_mainctx<'ctx>(&self, m: &'ctx MainCtx) -> &'ctx T
+    where
+        T: Sync,
+    {
+        // SAFETY: The cs is only used to get a shared reference to `T: Sync`.
+        // The inner data is never mutated after initialization.
+        self.as_ref_with_cs(unsafe { m.cs() })
+    }
+
+    /// Get a raw pointer to the inner data from an initialization context `InitCtx`.
+    ///
+    /// This can be used to program the address of the data into peripheral registers,
+    /// for example for DMA setup.
+    ///
+    /// The address is stable for the whole lifetime of the cell.
+    /// For a `static` cell that means the address is valid forever.
+    ///
+    /// The pointer is only valid for accesses after [Self::init] has been called.
+    #[inline(always)]
+    pub fn as_ptr(&self, _: &InitCtx<'_>) -> *const T {
+        self.inner_ptr()
+    }
+
+    /// Get a mutable raw pointer to the inner data from an initialization context `InitCtx`.
+    ///
+    /// See [Self::as_ptr].
+    ///
+    /// Writing through the pointer is only sound as long as no
+    /// reference to the inner data obtained from this cell is alive.
+    #[inline(always)]
+    pub fn as_mut_ptr(&self, _: &InitCtx<'_>) -> *mut T {
+        self.inner_ptr()
+    }
+
+    /// Get a raw pointer to the inner data.
+    ///
+    /// The pointer is only valid for accesses after [Self::init] has been called.
+    #[inline(always)]
+    pub(crate) fn inner_ptr(&self) -> *mut T {
+        self.0.get() as *mut T
+    }
 }
 
 impl<T> Drop for InitCtxCell<T> {
@@ -98,8 +281,152 @@ impl<T> Drop for InitCtxCell<T> {
 unsafe impl<T: Send> Send for InitCtxCell<T> {}
 
 // SAFETY: The cell only allows access with CriticalSection.
+// Critical sections of different contexts never overlap in time.
+// Therefore, `T: Send` is sufficient, like for `Mutex`. See the type documentation.
 unsafe impl<T: Send> Sync for InitCtxCell<T> {}
 
+enum InitState<T> {
+    Uninit,
+    Initialized(T),
+    Fallback(T),
+}
+
+/// Lazy initialization of static variables with a `Default` fallback.
+///
+/// This works like [InitCtxCell], but it is safe to skip the initialization.
+/// If the cell is accessed without being initialized with [Self::init],
+/// then it falls back to `T::default()`.
+/// That is useful for values that are initialized during normal boot,
+/// but that might not be initialized in a safe-mode boot.
+///
+/// The cell tracks whether it has been initialized.
+/// That costs up to one additional byte of RAM compared to [InitCtxCell].
+///
+/// See [InitCtxCell] for the thread safety rationale.
+pub struct InitOrDefault<T>(UnsafeCell<InitState<T>>);
+
+impl<T> InitOrDefault<T> {
+    /// Create a new uninitialized instance of [InitOrDefault].
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self(UnsafeCell::new(InitState::Uninit))
+    }
+
+    /// Initialize the cell with `inner` data and return a reference to it.
+    ///
+    /// Returns `inner` back, if the cell has already been initialized
+    /// or if it has already fallen back to the default value.
+    #[inline(always)]
+    pub fn init<'ctx>(&self, _: &'ctx InitCtx, inner: T) -> Result<&'ctx T, T> {
+        // SAFETY:
+        // This function can only be called from single threaded `InitCtx`
+        // with interrupts disabled. The `InitCtx` argument ensures that.
+        // There are no references to the inner data, because it is uninitialized.
+        // Accesses to initialized data do not modify it.
+        if !matches!(unsafe { &*self.0.get() }, InitState::Uninit) {
+            return Err(inner);
+        }
+        // SAFETY: The state is `Uninit`. There are no references to the inner data.
+        unsafe { *self.0.get() = InitState::Initialized(inner) };
+        // SAFETY: See above.
+        match unsafe { &*self.0.get() } {
+            InitState::Initialized(inner) => Ok(inner),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns `true`, if the cell has been initialized with [Self::init].
+    #[inline(always)]
+    pub fn is_initialized(&self, _: CriticalSection<'_>) -> bool {
+        // SAFETY: The state is only modified while it is `Uninit`,
+        // with the cs held. Reading it with the cs held is sound.
+        matches!(unsafe { &*self.0.get() }, InitState::Initialized(_))
+    }
+
+    /// Returns `true`, if the cell has fallen back to the default value.
+    #[inline(always)]
+    pub fn is_fallback(&self, _: CriticalSection<'_>) -> bool {
+        // SAFETY: See [Self::is_initialized].
+        matches!(unsafe { &*self.0.get() }, InitState::Fallback(_))
+    }
+}
+
+impl<T: Default> InitOrDefault<T> {
+    /// Get a reference to the inner data with the given critical section.
+    ///
+    /// If the cell has not been initialized, it falls back to `T::default()`.
+    #[inline(always)]
+    pub fn as_ref_with_cs<'cs>(&self, _: CriticalSection<'cs>) -> &'cs T {
+        // SAFETY:
+        // We are holding the cs. Interrupts are disabled.
+        // The state is only modified while it is `Uninit`.
+        // There are no references to the inner data in that state.
+        // After that the returned references are bound to the CriticalSection
+        // and the data is never modified again.
+        if matches!(unsafe { &*self.0.get() }, InitState::Uninit) {
+            // SAFETY: The state is `Uninit`. There are no references to the inner data.
+            unsafe { *self.0.get() = InitState::Fallback(T::default()) };
+        }
+        // SAFETY: See above.
+        match unsafe { &*self.0.get() } {
+            InitState::Initialized(inner) | InitState::Fallback(inner) => inner,
+            InitState::Uninit => unreachable!(),
+        }
+    }
+
+    /// Get a reference to the inner data from an initialization context `InitCtx`.
+    ///
+    /// If the cell has not been initialized, it falls back to `T::default()`.
+    #[inline(always)]
+    pub fn as_ref_with_initctx<'ctx>(&self, c: &'ctx InitCtx) -> &'ctx T {
+        self.as_ref_with_cs(c.cs())
+    }
+
+    /// Get a reference to the inner data from an interrupt context `IrqCtx`.
+    ///
+    /// If the cell has not been initialized, it falls back to `T::default()`.
+    #[inline(always)]
+    pub fn as_ref_with_irqctx<'ctx>(&self, c: &'ctx IrqCtx) -> &'ctx T {
+        self.as_ref_with_cs(c.cs())
+    }
+
+    /// Get a reference to the inner data from a main context `MainCtx`.
+    ///
+    /// If the cell has not been initialized, it falls back to `T::default()`.
+    /// This enters a critical section, because interrupt context
+    /// might fall back to the default value at the same time.
+    ///
+    /// `T` must be `Sync`, because interrupt context can access the inner data
+    /// at the same time via [Self::as_ref_with_irqctx].
+    #[inline(always)]
+    pub fn as_ref_with_mainctx<'a>(&'a self, _: &'a MainCtx) -> &'a T
+    where
+        T: Sync,
+    {
+        let inner = crate::with_cs(|cs| self.as_ref_with_cs(cs) as *const T);
+        // SAFETY: The state has left `Uninit` in the critical section above.
+        // After that the inner data is never modified again
+        // and it lives as long as the cell.
+        unsafe { &*inner }
+    }
+}
+
+impl<T> Default for InitOrDefault<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: If T is Send, then we can Send the whole object.
+// The object only contains T state.
+unsafe impl<T: Send> Send for InitOrDefault<T> {}
+
+// SAFETY: The cell only allows access with CriticalSection.
+// Critical sections of different contexts never overlap in time.
+// Therefore, `T: Send` is sufficient, like for `Mutex`. See [InitCtxCell].
+unsafe impl<T: Send> Sync for InitOrDefault<T> {}
+
 /// A cell that can only be accessed from `main()` context.
 ///
 /// There is no way to access `T` from interrupt context.
@@ -125,18 +452,151 @@ impl<T> MainCtxCell<T> {
     /// Replace the inner value with `inner` and return the old value.
     #[inline(always)]
     pub fn replace(&self, m: &MainCtx<'_>, inner: T) -> T {
+        trace_access!(self, Replace);
         // SAFETY: We only use the cs for the main context, where it is allowed to be used.
         self.inner.borrow(unsafe { m.cs() }).replace(inner)
     }
 
     /// Get a reference to the inner data from a main context `MainCtx`.
+    ///
+    /// The reference is bound to the lifetime of the cell.
+    /// It can't outlive a cell that is dropped before the `MainCtx`.
+    ///
+    /// ```compile_fail
+    /// use avr_context::{MainCtx, MainCtxCell};
+    ///
+    /// fn dangling<'cs>(m: &MainCtx<'cs>) -> &'cs u16 {
+    ///     let cell = MainCtxCell::new(42);
+    ///     cell.as_ref(m)
+    /// }
+    /// ```
     #[inline(always)]
-    pub fn as_ref<'cs>(&self, m: &MainCtx<'cs>) -> &'cs T {
-        // SAFETY: The returned reference is bound to the
-        // lifetime of the CriticalSection.
+    pub fn as_ref<'a>(&'a self, m: &MainCtx<'_>) -> &'a T {
+        trace_access!(self, Read);
+        // SAFETY: The returned reference is bound to the lifetime of the cell.
         // We only use the cs for the main context, where it is allowed to be used.
         unsafe { &*self.inner.borrow(m.cs()).as_ptr() as _ }
     }
+
+    /// Get a reference to a part of the inner data from a main context `MainCtx`.
+    ///
+    /// `f` projects the reference to the inner data to a reference to a part of it,
+    /// for example to a struct field, like `core::cell::Ref::map`.
+    ///
+    /// Like [Self::as_ref], the reference is bound to the lifetime of the cell:
+    ///
+    /// ```compile_fail
+    /// use avr_context::{MainCtx, MainCtxCell};
+    ///
+    /// fn dangling<'cs>(m: &MainCtx<'cs>) -> &'cs u8 {
+    ///     let cell = MainCtxCell::new((1_u8, 2_u8));
+    ///     cell.map_ref(m, |c| &c.0)
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn map_ref<'a, U: ?Sized>(&'a self, m: &MainCtx<'_>, f: impl FnOnce(&T) -> &U) -> &'a U {
+        f(self.as_ref(m))
+    }
+
+    /// Consume the cell and return the inner value.
+    ///
+    /// No context marker is needed, because ownership of the cell guarantees exclusive access.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner().into_inner()
+    }
+
+    /// Create a new cell from a raw `critical_section::Mutex`.
+    ///
+    /// This eases the migration of code that uses raw `Mutex<Cell<T>>` globals.
+    #[inline(always)]
+    pub const fn from_mutex(mutex: Mutex<Cell<T>>) -> Self {
+        Self { inner: mutex }
+    }
+
+    /// Consume the cell and return the raw `critical_section::Mutex`.
+    #[inline(always)]
+    pub fn into_mutex(self) -> Mutex<Cell<T>> {
+        self.inner
+    }
+
+    /// Get a reference to the raw `critical_section::Mutex` that backs this cell.
+    ///
+    /// This is useful for interoperability with drivers that expect a raw `Mutex<Cell<T>>`.
+    ///
+    /// # Safety
+    ///
+    /// The raw mutex does not check the execution context.
+    /// The caller must ensure that the data is only accessed from `main()` context.
+    /// In particular, the mutex must never be accessed from interrupt context.
+    #[inline(always)]
+    pub unsafe fn as_bare_mutex(&self) -> &Mutex<Cell<T>> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner value.
+    ///
+    /// No context marker is needed, because the `&mut self` borrow guarantees exclusive access.
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut().get_mut()
+    }
+}
+
+impl<T: Clone> MainCtxCell<T> {
+    /// Get a clone of the inner data from a main context `MainCtx`.
+    ///
+    /// This is useful for `Clone`-only types, where [Self::get] is not available.
+    #[inline(always)]
+    pub fn snapshot(&self, m: &MainCtx<'_>) -> T {
+        self.as_ref(m).clone()
+    }
+}
+
+impl<T: Default> MainCtxCell<T> {
+    /// Apply the fallible mutation `f` to the inner data from a main context `MainCtx`.
+    ///
+    /// This is useful for bounded containers like `heapless::Vec`,
+    /// where operations can be rejected.
+    /// The inner data is written back regardless of the result of `f`.
+    ///
+    /// The inner data is moved out of the cell while `f` runs.
+    /// Accesses to the cell from within `f` see `T::default()`.
+    #[inline(always)]
+    pub fn try_modify<R, E>(
+        &self,
+        m: &MainCtx<'_>,
+        f: impl FnOnce(&mut T) -> Result<R, E>,
+    ) -> Result<R, E> {
+        let mut inner = self.replace(m, T::default());
+        let ret = f(&mut inner);
+        drop(self.replace(m, inner));
+        ret
+    }
+
+    /// Reset the inner data to `T::default()` from a main context `MainCtx`.
+    ///
+    /// The old inner data is dropped.
+    #[inline(always)]
+    pub fn reset(&self, m: &MainCtx<'_>) {
+        drop(self.replace(m, T::default()));
+    }
+}
+
+impl<T: ZeroValue> MainCtxCell<T> {
+    /// Create a new `MainCtxCell` with the zero value of `T` as inner data.
+    ///
+    /// Unlike `MainCtxCell::new(T::default())`, this is usable in `const` context.
+    #[inline(always)]
+    pub const fn zeroed() -> Self {
+        Self::new(T::ZERO)
+    }
+
+    /// Set the inner data to the zero value of `T` from a main context `MainCtx`.
+    #[inline(always)]
+    pub fn zero(&self, m: &MainCtx<'_>) {
+        self.set(m, T::ZERO);
+    }
 }
 
 impl<T: Copy> MainCtxCell<T> {
@@ -159,6 +619,7 @@ impl<T: Copy> MainCtxCell<T> {
     /// Get a copy of the inner data from a main context `MainCtx`.
     #[inline(always)]
     pub fn get(&self, m: &MainCtx<'_>) -> T {
+        trace_access!(self, Read);
         // SAFETY: We only use the cs for the main context, where it is allowed to be used.
         self.inner.borrow(unsafe { m.cs() }).get()
     }
@@ -166,9 +627,801 @@ impl<T: Copy> MainCtxCell<T> {
     /// Set the inner data from a main context `MainCtx`.
     #[inline(always)]
     pub fn set(&self, m: &MainCtx<'_>, inner: T) {
+        trace_access!(self, Write);
         // SAFETY: We only use the cs for the main context, where it is allowed to be used.
         self.inner.borrow(unsafe { m.cs() }).set(inner);
     }
+
+    /// Call `f` with the raw bytes of the inner data from a main context `MainCtx`.
+    ///
+    /// The bytes are the in-memory representation of `T`.
+    /// Multi-byte values are stored in the native byte order of the target.
+    /// That is little endian on AVR, but may be different on other architectures.
+    ///
+    /// # Safety
+    ///
+    /// `T` must not contain padding bytes or otherwise uninitialized bytes.
+    /// Primitive integer types and arrays of them are fine.
+    /// Structs may contain padding bytes, depending on their layout.
+    ///
+    /// `f` must not modify this cell.
+    #[inline(always)]
+    pub unsafe fn with_bytes<R>(&self, m: &MainCtx<'_>, f: impl FnOnce(&[u8]) -> R) -> R {
+        trace_access!(self, Read);
+        // SAFETY: We only use the cs for the main context, where it is allowed to be used.
+        let ptr = self.inner.borrow(unsafe { m.cs() }).as_ptr() as *const u8;
+        // SAFETY:
+        // The pointer points to a valid `T` that is `size_of::<T>()` bytes long.
+        // Our caller guarantees that all bytes are initialized
+        // and that the cell is not modified while the slice is alive.
+        let bytes = unsafe { core::slice::from_raw_parts(ptr, size_of::<T>()) };
+        f(bytes)
+    }
+}
+
+impl<T: Copy + PartialEq> MainCtxCell<T> {
+    /// Set the inner data from a main context `MainCtx`
+    /// and return whether it differed from the previous inner data.
+    ///
+    /// The inner data is always written, even if it did not change.
+    #[inline(always)]
+    pub fn set_and_changed(&self, m: &MainCtx<'_>, inner: T) -> bool {
+        self.replace(m, inner) != inner
+    }
+}
+
+impl<T: Copy> MainCtxCell<Option<T>> {
+    /// Get the inner value and initialize it with `init` first, if it is `None`.
+    ///
+    /// This provides `OnceCell` like lazy initialization for values that
+    /// are not known in the initialization phase.
+    ///
+    /// The value is returned by copy, because the cell can be overwritten
+    /// with [Self::set] or [Self::replace] later on.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `init` initializes the same cell recursively.
+    #[inline(always)]
+    pub fn ensure(&self, m: &MainCtx<'_>, init: impl FnOnce() -> T) -> T {
+        if let Some(inner) = self.get(m) {
+            return inner;
+        }
+        let inner = init();
+        // Don't silently overwrite the value that a recursive `init` has stored.
+        assert!(
+            self.get(m).is_none(),
+            "MainCtxCell::ensure: reentrant initialization"
+        );
+        self.set(m, Some(inner));
+        inner
+    }
+}
+
+impl<T: ?Sized + 'static> MainCtxCell<&'static T> {
+    /// Replace the stored reference with `new`,
+    /// if the stored reference is the same pointer as `expected`.
+    ///
+    /// Only the addresses are compared with `core::ptr::addr_eq`.
+    /// The metadata of fat pointers is ignored:
+    /// For slices the length is not compared
+    /// and for trait objects the vtable is not compared,
+    /// because the same object can have different vtable pointers.
+    ///
+    /// Returns `true`, if the reference has been replaced.
+    #[inline(always)]
+    pub fn cas_ptr(&self, m: &MainCtx<'_>, expected: &'static T, new: &'static T) -> bool {
+        let matches = core::ptr::addr_eq(self.get(m), expected);
+        if matches {
+            self.set(m, new);
+        }
+        matches
+    }
+}
+
+impl<T, const N: usize> MainCtxCell<[T; N]> {
+    /// Get references to all array elements from a main context `MainCtx`.
+    ///
+    /// Like [MainCtxCell::as_ref], the references are bound to the lifetime of the cell:
+    ///
+    /// ```compile_fail
+    /// use avr_context::{MainCtx, MainCtxCell};
+    ///
+    /// fn dangling<'cs>(m: &MainCtx<'cs>) -> [&'cs u8; 2] {
+    ///     let cell = MainCtxCell::new([1_u8, 2]);
+    ///     cell.refs(m)
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn refs<'a>(&'a self, m: &MainCtx<'_>) -> [&'a T; N] {
+        self.as_ref(m).each_ref()
+    }
+}
+
+impl<const N: usize> MainCtxCell<[u8; N]> {
+    /// Calculate the Fletcher-16 checksum of the bytes from a main context `MainCtx`.
+    ///
+    /// See [crate::integrity::Checked] for a byte array with a stored checksum.
+    #[inline(always)]
+    pub fn checksum(&self, m: &MainCtx<'_>) -> u16 {
+        crate::integrity::fletcher16(self.as_ref(m))
+    }
+}
+
+impl<T: Hash> MainCtxCell<T> {
+    /// Calculate the FNV-1a hash of the inner value from a main context `MainCtx`.
+    ///
+    /// This is useful for detecting changes of large values cheaply
+    /// by comparing the hash with a previously calculated hash.
+    ///
+    /// See [crate::integrity::Fnv1a].
+    #[inline(always)]
+    pub fn hash(&self, m: &MainCtx<'_>) -> u32 {
+        let mut hasher = crate::integrity::Fnv1a::new();
+        self.as_ref(m).hash(&mut hasher);
+        hasher.finish32()
+    }
+}
+
+impl<const N: usize> MainCtxCell<([u8; N], usize)> {
+    /// Drain bytes from the start of a length prefixed buffer from a main context `MainCtx`.
+    ///
+    /// The cell holds a buffer and the number of valid bytes at the start of the buffer.
+    /// Up to `out.len()` bytes are copied from the start of the buffer to `out`.
+    /// The remaining valid bytes are moved to the start of the buffer
+    /// and the length is reduced accordingly.
+    ///
+    /// Returns the number of bytes that have been copied to `out`.
+    #[inline(always)]
+    pub fn drain_prefix(&self, m: &MainCtx<'_>, out: &mut [u8]) -> usize {
+        let (mut buf, len) = self.get(m);
+        let len = len.min(N);
+        let count = len.min(out.len());
+        out[..count].copy_from_slice(&buf[..count]);
+        buf.copy_within(count..len, 0);
+        self.set(m, (buf, len - count));
+        count
+    }
+}
+
+impl<T: Copy, const N: usize> MainCtxCell<[T; N]> {
+    /// Get a copy of the array element at `index` from a main context `MainCtx`.
+    ///
+    /// Only the element is copied, not the whole array.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `index` is out of bounds.
+    #[inline(always)]
+    pub fn index(&self, m: &MainCtx<'_>, index: usize) -> T {
+        trace_access!(self, Read);
+        // SAFETY: We only use the cs for the main context, where it is allowed to be used.
+        let cells: &Cell<[T]> = self.inner.borrow(unsafe { m.cs() });
+        cells.as_slice_of_cells()[index].get()
+    }
+
+    /// Set the array element at `index` from a main context `MainCtx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `index` is out of bounds.
+    #[inline(always)]
+    pub fn set_index(&self, m: &MainCtx<'_>, index: usize, inner: T) {
+        trace_access!(self, Write);
+        // SAFETY: We only use the cs for the main context, where it is allowed to be used.
+        let cells: &Cell<[T]> = self.inner.borrow(unsafe { m.cs() });
+        cells.as_slice_of_cells()[index].set(inner);
+    }
+
+    /// Set all array elements to `val` from a main context `MainCtx`.
+    #[inline(always)]
+    pub fn fill(&self, m: &MainCtx<'_>, val: T) {
+        trace_access!(self, Write);
+        // SAFETY: We only use the cs for the main context, where it is allowed to be used.
+        let cells: &Cell<[T]> = self.inner.borrow(unsafe { m.cs() });
+        for cell in cells.as_slice_of_cells() {
+            cell.set(val);
+        }
+    }
+
+    /// Modify each array element with `f` from a main context `MainCtx`.
+    ///
+    /// `f` is called with a copy of each element, which is written back after `f` returns.
+    #[inline(always)]
+    pub fn with_each_mut(&self, m: &MainCtx<'_>, mut f: impl FnMut(&mut T)) {
+        trace_access!(self, Modify);
+        // SAFETY: We only use the cs for the main context, where it is allowed to be used.
+        let cells: &Cell<[T]> = self.inner.borrow(unsafe { m.cs() });
+        for cell in cells.as_slice_of_cells() {
+            let mut elem = cell.get();
+            f(&mut elem);
+            cell.set(elem);
+        }
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> MainCtxCell<heapless::String<N>> {
+    /// Clear the string and format new contents into it with `f`
+    /// from a main context `MainCtx`.
+    ///
+    /// The string is moved out of the cell while `f` runs.
+    /// Accesses to the cell from within `f` see an empty string.
+    #[inline(always)]
+    pub fn format_into<R>(
+        &self,
+        m: &MainCtx<'_>,
+        f: impl FnOnce(&mut heapless::String<N>) -> R,
+    ) -> R {
+        let mut s = self.replace(m, heapless::String::new());
+        s.clear();
+        let ret = f(&mut s);
+        drop(self.replace(m, s));
+        ret
+    }
+}
+
+#[cfg(feature = "fixed")]
+impl<T: fixed::traits::Fixed> MainCtxCell<T> {
+    /// Add `v` to the fixed-point inner value from a main context `MainCtx`.
+    ///
+    /// Returns the new value.
+    ///
+    /// # Panics
+    ///
+    /// Panics on overflow, if overflow checks are enabled.
+    #[inline(always)]
+    pub fn add(&self, m: &MainCtx<'_>, v: T) -> T {
+        let new = self.get(m) + v;
+        self.set(m, new);
+        new
+    }
+
+    /// Multiply the fixed-point inner value by `v` from a main context `MainCtx`.
+    ///
+    /// Returns the new value.
+    ///
+    /// # Panics
+    ///
+    /// Panics on overflow, if overflow checks are enabled.
+    #[inline(always)]
+    pub fn mul(&self, m: &MainCtx<'_>, v: T) -> T {
+        let new = self.get(m) * v;
+        self.set(m, new);
+        new
+    }
+
+    /// Add `v` to the fixed-point inner value with saturation from a main context `MainCtx`.
+    ///
+    /// Returns the new value.
+    #[inline(always)]
+    pub fn saturating_add(&self, m: &MainCtx<'_>, v: T) -> T {
+        let new = self.get(m).saturating_add(v);
+        self.set(m, new);
+        new
+    }
+
+    /// Multiply the fixed-point inner value by `v` with saturation from a main context `MainCtx`.
+    ///
+    /// Returns the new value.
+    #[inline(always)]
+    pub fn saturating_mul(&self, m: &MainCtx<'_>, v: T) -> T {
+        let new = self.get(m).saturating_mul(v);
+        self.set(m, new);
+        new
+    }
+
+    /// Multiply the fixed-point inner value by `a` and add `b` from a main context `MainCtx`.
+    ///
+    /// This is a typical filter or PID update step. The result saturates.
+    ///
+    /// Returns the new value.
+    #[inline(always)]
+    pub fn saturating_mul_add(&self, m: &MainCtx<'_>, a: T, b: T) -> T {
+        let new = self.get(m).saturating_mul(a).saturating_add(b);
+        self.set(m, new);
+        new
+    }
+}
+
+impl<T: Copy> MainCtxCell<Wrapping<T>>
+where
+    Wrapping<T>: Add<Output = Wrapping<T>>,
+{
+    /// Add `v` to the inner value with wrapping around on overflow
+    /// from a main context `MainCtx`.
+    #[inline(always)]
+    pub fn wrapping_add(&self, m: &MainCtx<'_>, v: T) {
+        self.set(m, self.get(m) + Wrapping(v));
+    }
+
+    /// Get a copy of the raw inner value without the `Wrapping`
+    /// from a main context `MainCtx`.
+    #[inline(always)]
+    pub fn get_raw(&self, m: &MainCtx<'_>) -> T {
+        self.get(m).0
+    }
+}
+
+/// An array of elements that can only be accessed from `main()` context.
+///
+/// This is a [MainCtxCell] of an array with element based accessors.
+/// `core::ops::Index` can't be implemented, because the accesses need a context marker.
+#[repr(transparent)]
+pub struct MainCtxCellArray<T, const N: usize>(MainCtxCell<[T; N]>);
+
+impl<T, const N: usize> MainCtxCellArray<T, N> {
+    /// Create a new array cell with the `inner` elements.
+    #[inline(always)]
+    pub const fn new(inner: [T; N]) -> Self {
+        Self(MainCtxCell::new(inner))
+    }
+
+    /// Get the number of array elements.
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    /// Returns `true`, if the array has no elements.
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Get the underlying [MainCtxCell] of the whole array.
+    #[inline(always)]
+    pub fn as_cell(&self) -> &MainCtxCell<[T; N]> {
+        &self.0
+    }
+}
+
+impl<T: Copy, const N: usize> MainCtxCellArray<T, N> {
+    /// Create a new array cell with all elements set to `inner`.
+    #[inline(always)]
+    pub const fn new_filled(inner: T) -> Self {
+        Self::new([inner; N])
+    }
+
+    /// Get a copy of the element at `index` from a main context `MainCtx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `index` is out of bounds.
+    #[inline(always)]
+    pub fn get(&self, m: &MainCtx<'_>, index: usize) -> T {
+        self.0.index(m, index)
+    }
+
+    /// Set the element at `index` from a main context `MainCtx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `index` is out of bounds.
+    #[inline(always)]
+    pub fn set(&self, m: &MainCtx<'_>, index: usize, inner: T) {
+        self.0.set_index(m, index, inner);
+    }
+
+    /// Modify the element at `index` with `f` from a main context `MainCtx`.
+    ///
+    /// `f` is called with a copy of the element, which is written back after `f` returns.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `index` is out of bounds.
+    #[inline(always)]
+    pub fn update<R>(&self, m: &MainCtx<'_>, index: usize, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut elem = self.get(m, index);
+        let ret = f(&mut elem);
+        self.set(m, index, elem);
+        ret
+    }
+
+    /// Get a copy of the whole array from a main context `MainCtx`.
+    #[inline(always)]
+    pub fn get_all(&self, m: &MainCtx<'_>) -> [T; N] {
+        self.0.get(m)
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Default for MainCtxCellArray<T, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new_filled(T::default())
+    }
+}
+
+/// A cell that can only be accessed from interrupt context.
+///
+/// There is no way to access `T` from `main()` context.
+/// Interrupts are disabled in interrupt context.
+/// Therefore, all allowed accesses to `T` (from interrupt context)
+/// do not need to disable interrupts or take any other measures
+/// against interruption.
+///
+/// The data can also be initialized from the initialization context `InitCtx`.
+#[repr(transparent)]
+pub struct IrqCtxCell<T> {
+    inner: Mutex<Cell<T>>,
+}
+
+impl<T> IrqCtxCell<T> {
+    /// Create a new `IrqCtxCell` with the given initial value.
+    #[inline(always)]
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner: Mutex::new(Cell::new(inner)),
+        }
+    }
+
+    /// Replace the inner value with `inner` and return the old value.
+    #[inline(always)]
+    pub fn replace(&self, irq: &IrqCtx<'_>, inner: T) -> T {
+        self.inner.borrow(irq.cs()).replace(inner)
+    }
+
+    /// Initialize the inner value with `inner` from an initialization context `InitCtx`.
+    #[inline(always)]
+    pub fn init(&self, c: &InitCtx<'_>, inner: T) {
+        self.inner.borrow(c.cs()).set(inner);
+    }
+
+    /// Modify the inner data in place with `f` from an interrupt context `IrqCtx`.
+    ///
+    /// This requires a `&mut IrqCtx`.
+    /// All accesses to an `IrqCtxCell` need a reference to the `IrqCtx`.
+    /// Therefore, no other access to the cell can happen while `f` runs.
+    /// A `&IrqCtx` would not be sufficient, because `f` could then
+    /// capture it and access the cell while the mutable reference is alive.
+    ///
+    /// [crate::define_isr] passes a `&mut IrqCtx` to the interrupt service routine.
+    #[inline(always)]
+    pub fn with_mut<R>(&self, irq: &mut IrqCtx<'_>, f: impl FnOnce(&mut T) -> R) -> R {
+        let ptr = self.inner.borrow(irq.cs()).as_ptr();
+        // SAFETY:
+        // The `&mut IrqCtx` is borrowed exclusively until `f` returns.
+        // Therefore, no other reference to the inner data can be created while `f` runs.
+        f(unsafe { &mut *ptr })
+    }
+}
+
+impl<T: Copy> IrqCtxCell<T> {
+    /// Get a copy of the inner data from an interrupt context `IrqCtx`.
+    #[inline(always)]
+    pub fn get(&self, irq: &IrqCtx<'_>) -> T {
+        self.inner.borrow(irq.cs()).get()
+    }
+
+    /// Set the inner data from an interrupt context `IrqCtx`.
+    #[inline(always)]
+    pub fn set(&self, irq: &IrqCtx<'_>, inner: T) {
+        self.inner.borrow(irq.cs()).set(inner);
+    }
+}
+
+impl<T: Copy, const N: usize> IrqCtxCell<[T; N]> {
+    /// Initialize all array elements by copying `values` from an initialization context `InitCtx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the length of `values` is not `N`.
+    #[inline(always)]
+    pub fn init_bulk(&self, c: &InitCtx<'_>, values: &[T]) {
+        assert_eq!(values.len(), N, "init_bulk: values must have length N");
+        let cells: &Cell<[T]> = self.inner.borrow(c.cs());
+        for (cell, value) in cells.as_slice_of_cells().iter().zip(values) {
+            cell.set(*value);
+        }
+    }
+}
+
+/// A cell that can only be accessed from the interrupt context with the matching `Tag`.
+///
+/// This is the tagged variant of [IrqCtxCell] for systems with nested interrupts.
+/// Accesses from interrupt service routines with a different tag
+/// and from `main()` context are prevented at compile time.
+/// See [TaggedIrqCtx].
+///
+/// The data can also be initialized from the initialization context `InitCtx`.
+pub struct TaggedIrqCtxCell<Tag, T> {
+    inner: Mutex<Cell<T>>,
+    _tag: PhantomData<fn() -> Tag>,
+}
+
+impl<Tag, T> TaggedIrqCtxCell<Tag, T> {
+    /// Create a new `TaggedIrqCtxCell` with the given initial value.
+    #[inline(always)]
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner: Mutex::new(Cell::new(inner)),
+            _tag: PhantomData,
+        }
+    }
+
+    /// Replace the inner value with `inner` and return the old value.
+    #[inline(always)]
+    pub fn replace(&self, irq: &TaggedIrqCtx<'_, Tag>, inner: T) -> T {
+        self.inner.borrow(irq.cs()).replace(inner)
+    }
+
+    /// Initialize the inner value with `inner` from an initialization context `InitCtx`.
+    #[inline(always)]
+    pub fn init(&self, c: &InitCtx<'_>, inner: T) {
+        self.inner.borrow(c.cs()).set(inner);
+    }
+}
+
+impl<Tag, T: Copy> TaggedIrqCtxCell<Tag, T> {
+    /// Get a copy of the inner data from the tagged interrupt context.
+    #[inline(always)]
+    pub fn get(&self, irq: &TaggedIrqCtx<'_, Tag>) -> T {
+        self.inner.borrow(irq.cs()).get()
+    }
+
+    /// Set the inner data from the tagged interrupt context.
+    #[inline(always)]
+    pub fn set(&self, irq: &TaggedIrqCtx<'_, Tag>, inner: T) {
+        self.inner.borrow(irq.cs()).set(inner);
+    }
+}
+
+/// Get copies of the inner data of two [MainCtxCell]s as a pair.
+#[inline(always)]
+pub fn get2<A: Copy, B: Copy>(a: &MainCtxCell<A>, b: &MainCtxCell<B>, m: &MainCtx<'_>) -> (A, B) {
+    (a.get(m), b.get(m))
+}
+
+/// Set the inner data of two [MainCtxCell]s from a pair.
+#[inline(always)]
+pub fn set2<A: Copy, B: Copy>(
+    a: &MainCtxCell<A>,
+    b: &MainCtxCell<B>,
+    m: &MainCtx<'_>,
+    (va, vb): (A, B),
+) {
+    a.set(m, va);
+    b.set(m, vb);
+}
+
+/// Call `f` for each cell in `cells`.
+///
+/// This is useful for operating on an array of identical channels.
+/// `f` gets the `main()` context, so that it can access the cell.
+#[inline(always)]
+pub fn for_each<T>(
+    cells: &[MainCtxCell<T>],
+    m: &MainCtx<'_>,
+    mut f: impl FnMut(&MainCtxCell<T>, &MainCtx<'_>),
+) {
+    for cell in cells {
+        f(cell, m);
+    }
+}
+
+/// A cell holding a `&'static mut T` reference that can only be accessed from `main()` context.
+///
+/// `T` may be unsized.
+/// This is useful for storing dynamically dispatched handlers,
+/// e.g. `MainCtxDynCell<dyn State + Send>` for the current state of a state machine.
+#[repr(transparent)]
+pub struct MainCtxDynCell<T: ?Sized + 'static> {
+    inner: MainCtxCell<Option<&'static mut T>>,
+}
+
+impl<T: ?Sized + 'static> MainCtxDynCell<T> {
+    /// Create a new empty `MainCtxDynCell`.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            inner: MainCtxCell::new(None),
+        }
+    }
+
+    /// Store the reference `inner` and return the previously stored reference.
+    #[inline(always)]
+    pub fn set(&self, m: &MainCtx<'_>, inner: &'static mut T) -> Option<&'static mut T> {
+        self.inner.replace(m, Some(inner))
+    }
+
+    /// Remove the stored reference and return it.
+    #[inline(always)]
+    pub fn take(&self, m: &MainCtx<'_>) -> Option<&'static mut T> {
+        self.inner.replace(m, None)
+    }
+
+    /// Call `f` with mutable access to the referenced `T` from a main context `MainCtx`.
+    ///
+    /// Returns `None` without calling `f`, if the cell is empty.
+    ///
+    /// The reference is removed from the cell while `f` runs.
+    /// Therefore, a nested call to [Self::with] from within `f` returns `None`.
+    /// If a new reference is stored by `f`, then the new reference is kept.
+    #[inline(always)]
+    pub fn with<R>(&self, m: &MainCtx<'_>, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let inner = self.take(m)?;
+        let ret = f(inner);
+        if let Some(newer) = self.inner.replace(m, Some(inner)) {
+            self.inner.replace(m, Some(newer));
+        }
+        Some(ret)
+    }
+}
+
+impl<T: ?Sized + 'static> Default for MainCtxDynCell<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Define a struct of [MainCtxCell]s with `const` initializers.
+///
+/// Each field is declared with its inner type and its initial value.
+/// The macro generates the struct with a [MainCtxCell] for each field,
+/// a `const fn new()` constructor that initializes all cells
+/// and a `Default` implementation.
+///
+/// # Example
+///
+/// ```
+/// use avr_context::{MainCtx, main_cells};
+///
+/// main_cells! {
+///     /// Device state.
+///     pub struct DeviceState {
+///         pub speed: u16 = 100,
+///         pub mode: [u8; 2] = [1, 2],
+///         enabled: bool = false,
+///     }
+/// }
+///
+/// static STATE: DeviceState = DeviceState::new();
+///
+/// fn enable(m: &MainCtx<'_>) {
+///     STATE.enabled.set(m, true);
+///     STATE.speed.set(m, 200);
+/// }
+/// ```
+#[macro_export]
+macro_rules! main_cells {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_attr:meta])*
+                $field_vis:vis $field:ident : $ty:ty = $init:expr
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $(
+                $(#[$field_attr])*
+                $field_vis $field: $crate::MainCtxCell<$ty>,
+            )*
+        }
+
+        impl $name {
+            /// Create a new instance with all cells set to their initial values.
+            #[inline(always)]
+            pub const fn new() -> Self {
+                Self {
+                    $(
+                        $field: $crate::MainCtxCell::new($init),
+                    )*
+                }
+            }
+        }
+
+        impl ::core::default::Default for $name {
+            #[inline(always)]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+/// Define a configuration struct that is stored in a static [InitOrDefault].
+///
+/// The macro generates the struct with the given fields,
+/// a private static [InitOrDefault] with the given name for the configuration
+/// and the following associated functions:
+///
+/// - `init(c: &InitCtx, config: Self) -> Result<&Self, Self>` to initialize the configuration.
+///   Returns `config` back, if the configuration has already been initialized
+///   or if it has already fallen back to the default value.
+/// - `get(m: &MainCtx) -> &Self` to get a reference to the whole configuration.
+/// - One getter for each field that returns a reference to the field.
+///   The getter has the same name and visibility as the field.
+///
+/// The fields must therefore not be named `init` or `get`.
+///
+/// The struct must implement `Default`.
+/// If the configuration has not been initialized with `init`,
+/// the getters fall back to the default configuration.
+///
+/// # Example
+///
+/// ```
+/// use avr_context::{InitCtx, MainCtx, typed_config};
+///
+/// typed_config! {
+///     /// Firmware configuration.
+///     #[derive(Default)]
+///     pub struct Config {
+///         pub baud: u32,
+///         pub address: u8,
+///         name: [u8; 4],
+///     }
+///     static CONFIG;
+/// }
+///
+/// fn init(c: &InitCtx<'_>) {
+///     let _ = Config::init(c, Config { baud: 9600, address: 0x12, name: *b"node" });
+/// }
+///
+/// fn main_loop(m: &MainCtx<'_>) {
+///     let baud: u32 = *Config::baud(m);
+///     let name: &[u8; 4] = Config::name(m);
+///     // ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! typed_config {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_attr:meta])*
+                $field_vis:vis $field:ident : $ty:ty
+            ),* $(,)?
+        }
+        static $static:ident;
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $(
+                $(#[$field_attr])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        static $static: $crate::InitOrDefault<$name> = $crate::InitOrDefault::new();
+
+        impl $name {
+            /// Initialize the configuration.
+            ///
+            /// Returns `config` back, if the configuration has already been initialized
+            /// or if it has already fallen back to the default value.
+            #[inline(always)]
+            #[allow(dead_code)]
+            $vis fn init<'ctx>(
+                c: &'ctx $crate::InitCtx<'_>,
+                config: Self,
+            ) -> ::core::result::Result<&'ctx Self, Self> {
+                $static.init(c, config)
+            }
+
+            /// Get a reference to the configuration.
+            ///
+            /// Falls back to the default configuration, if it has not been initialized.
+            #[inline(always)]
+            $vis fn get<'ctx>(m: &'ctx $crate::MainCtx<'_>) -> &'ctx Self {
+                $static.as_ref_with_mainctx(m)
+            }
+
+            $(
+                #[doc = concat!("Get a reference to the `", stringify!($field), "` field.")]
+                #[inline(always)]
+                #[allow(dead_code)]
+                $field_vis fn $field<'ctx>(m: &'ctx $crate::MainCtx<'_>) -> &'ctx $ty {
+                    &Self::get(m).$field
+                }
+            )*
+        }
+    };
 }
 
 // vim: ts=4 sw=4 expandtab
@@ -0,0 +1,136 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Code generation probes.
+//!
+//! This module is only available with the `codegen-test` feature.
+//!
+//! Each probe wraps one basic operation of this crate into a
+//! `#[no_mangle]` and `#[inline(never)]` function.
+//! That makes it possible to inspect the generated machine code of each operation
+//! and to verify that the operations optimize to plain memory loads and stores.
+//!
+//! To inspect the AVR assembly, build the crate for an AVR target with assembly output:
+//!
+//! ```text
+//! cargo +nightly rustc --release --features codegen-test \
+//!     --target avr-none -Z build-std=core -- \
+//!     -C target-cpu=atmega328p --emit asm
+//! ```
+//!
+//! Then look for the `avr_context_codegen_*` symbols in the generated `.s` file
+//! in `target/avr-none/release/deps/`.
+//! For example, `avr_context_codegen_main_cell_get` is expected to consist of
+//! nothing but the 16 bit memory load and the `ret`.
+
+use crate::{
+    cell::{InitCtxCell, IrqCtxCell, MainCtxCell},
+    context::{IrqCtx, MainCtx},
+};
+
+/// Construct and drop a `MainCtx`.
+///
+/// Expected: No code besides `ret`.
+///
+/// # Safety
+///
+/// The safety contract of [MainCtx::new] must be upheld.
+#[unsafe(no_mangle)]
+#[inline(never)]
+pub unsafe fn avr_context_codegen_main_ctx_new_drop() {
+    // SAFETY: The safety contract of the called function is equal to ours.
+    let m = unsafe { MainCtx::new() };
+    drop(m);
+}
+
+/// Construct and drop an `IrqCtx`.
+///
+/// Expected: No code besides `ret`.
+///
+/// # Safety
+///
+/// The safety contract of [IrqCtx::new] must be upheld.
+#[unsafe(no_mangle)]
+#[inline(never)]
+pub unsafe fn avr_context_codegen_irq_ctx_new_drop() {
+    // SAFETY: The safety contract of the called function is equal to ours.
+    let irq = unsafe { IrqCtx::new() };
+    drop(irq);
+}
+
+/// Read a `MainCtxCell`.
+///
+/// Expected: Plain memory load.
+#[unsafe(no_mangle)]
+#[inline(never)]
+pub fn avr_context_codegen_main_cell_get(cell: &MainCtxCell<u16>, m: &MainCtx<'_>) -> u16 {
+    cell.get(m)
+}
+
+/// Write a `MainCtxCell`.
+///
+/// Expected: Plain memory store.
+#[unsafe(no_mangle)]
+#[inline(never)]
+pub fn avr_context_codegen_main_cell_set(cell: &MainCtxCell<u16>, m: &MainCtx<'_>, value: u16) {
+    cell.set(m, value);
+}
+
+/// Read an `IrqCtxCell`.
+///
+/// Expected: Plain memory load.
+#[unsafe(no_mangle)]
+#[inline(never)]
+pub fn avr_context_codegen_irq_cell_get(cell: &IrqCtxCell<u16>, irq: &IrqCtx<'_>) -> u16 {
+    cell.get(irq)
+}
+
+/// Write an `IrqCtxCell`.
+///
+/// Expected: Plain memory store.
+#[unsafe(no_mangle)]
+#[inline(never)]
+pub fn avr_context_codegen_irq_cell_set(cell: &IrqCtxCell<u16>, irq: &IrqCtx<'_>, value: u16) {
+    cell.set(irq, value);
+}
+
+/// Read an `InitCtxCell` from interrupt context.
+///
+/// Expected: Plain memory load.
+#[unsafe(no_mangle)]
+#[inline(never)]
+pub fn avr_context_codegen_init_cell_get(cell: &InitCtxCell<u16>, irq: &IrqCtx<'_>) -> u16 {
+    *cell.as_ref_with_irqctx(irq)
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_codegen_probes() {
+        unsafe { avr_context_codegen_main_ctx_new_drop() };
+        unsafe { avr_context_codegen_irq_ctx_new_drop() };
+
+        let m = unsafe { MainCtx::new() };
+        let main_cell = MainCtxCell::new(1);
+        avr_context_codegen_main_cell_set(&main_cell, &m, 2);
+        assert_eq!(avr_context_codegen_main_cell_get(&main_cell, &m), 2);
+
+        let irq = unsafe { IrqCtx::new() };
+        let irq_cell = IrqCtxCell::new(3);
+        avr_context_codegen_irq_cell_set(&irq_cell, &irq, 4);
+        assert_eq!(avr_context_codegen_irq_cell_get(&irq_cell, &irq), 4);
+
+        fn init(c: &crate::InitCtx<'_>, cell: &InitCtxCell<u16>) {
+            cell.init(c, 5);
+        }
+        let init_cell = unsafe { InitCtxCell::uninit() };
+        let _ = unsafe { MainCtx::new_with_init(init, &init_cell) };
+        assert_eq!(avr_context_codegen_init_cell_get(&init_cell, &irq), 5);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -4,30 +4,77 @@
 
 //! Context marker types.
 
-use crate::CriticalSection;
-use core::sync::atomic::{Ordering::SeqCst, fence};
+use crate::{
+    CriticalSection, Mutex,
+    cell::{IrqCtxCell, MainCtxCell},
+    flag::IrqFlag,
+    time::Clock,
+    with_cs,
+};
+use core::{
+    cell::Cell,
+    marker::PhantomData,
+    sync::atomic::{Ordering::SeqCst, fence},
+};
 
 /// `main()` context marker.
 ///
 /// Possession of this marker, or a reference to it,
 /// guarantees execution in the `main()` context.
-pub struct MainCtx<'cs>(CriticalSection<'cs>);
+pub struct MainCtx<'cs>(CriticalSection<'cs>, CtxId);
 
 /// Interrupt context marker.
 ///
 /// Possession of this marker, or a reference to it,
 /// guarantees execution in interrupt context.
-pub struct IrqCtx<'cs>(CriticalSection<'cs>);
+pub struct IrqCtx<'cs>(CriticalSection<'cs>, CtxId);
+
+/// Identity of a context marker instance for diagnostic assertions.
+///
+/// Each constructed [MainCtx] and [IrqCtx] gets a new unique id in debug builds.
+/// That way tests can verify that the expected context has been passed through a call chain.
+///
+/// In release builds the id is a zero sized type and all ids compare equal.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CtxId {
+    #[cfg(debug_assertions)]
+    id: u32,
+}
+
+impl CtxId {
+    #[inline(always)]
+    #[allow(unused_variables)]
+    fn next(cs: CriticalSection<'_>) -> Self {
+        #[cfg(all(debug_assertions, target_has_atomic = "32"))]
+        let id = {
+            static NEXT_ID: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(0);
+            NEXT_ID.fetch_add(1, core::sync::atomic::Ordering::Relaxed)
+        };
+        #[cfg(all(debug_assertions, not(target_has_atomic = "32")))]
+        let id = {
+            // Interrupts are disabled during context construction.
+            static NEXT_ID: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+            let next = NEXT_ID.borrow(cs);
+            let id = next.get();
+            next.set(id.wrapping_add(1));
+            id
+        };
+        Self {
+            #[cfg(debug_assertions)]
+            id,
+        }
+    }
+}
 
 macro_rules! impl_context {
-    ($name:ident) => {
+    ($name:ident $(, on_drop: $on_drop:expr)?) => {
         impl<'cs> $name<'cs> {
             #[inline(always)]
             #[allow(unreachable_code)]
             #[allow(unused_variables)]
             unsafe fn internal_new() -> Self {
                 // This crate is unsound in multi processor or multi threading environments.
-                #[cfg(not(any(target_arch = "avr", test)))]
+                #[cfg(not(any(target_arch = "avr", test, feature = "std-test")))]
                 panic!("This crate is only designed to be sound on target_arch=avr");
 
                 // SAFETY:
@@ -52,7 +99,15 @@ macro_rules! impl_context {
                 // context are moved outside.
                 fence(SeqCst);
 
-                Self(cs)
+                Self(cs, CtxId::next(cs))
+            }
+
+            /// Get the identity of this context marker instance.
+            ///
+            /// See [CtxId].
+            #[inline(always)]
+            pub fn id(&self) -> CtxId {
+                self.1
             }
         }
 
@@ -62,13 +117,86 @@ macro_rules! impl_context {
                 // Barrier to ensure that no memory accesses from inside of the
                 // context are moved outside.
                 fence(SeqCst);
+                $( $on_drop; )?
             }
         }
     };
 }
 
 impl_context!(MainCtx);
-impl_context!(IrqCtx);
+impl_context!(IrqCtx, on_drop: irq_nesting::leave());
+
+/// Debug tracking of the interrupt context nesting depth.
+mod irq_nesting {
+    #[cfg(debug_assertions)]
+    use core::cell::Cell;
+
+    /// Current depth and maximum observed depth.
+    #[cfg(all(debug_assertions, target_arch = "avr"))]
+    static DEPTH: crate::Mutex<Cell<(u8, u8)>> = crate::Mutex::new(Cell::new((0, 0)));
+
+    // Host tests run in parallel threads. Track each thread separately.
+    #[cfg(all(debug_assertions, not(target_arch = "avr")))]
+    std::thread_local! {
+        static DEPTH: Cell<(u8, u8)> = const { Cell::new((0, 0)) };
+    }
+
+    #[cfg(debug_assertions)]
+    #[inline(always)]
+    fn with_depth<R>(f: impl FnOnce(&Cell<(u8, u8)>) -> R) -> R {
+        #[cfg(target_arch = "avr")]
+        {
+            // Nested interrupts may be enabled while the context is dropped.
+            // Enter a real critical section for the read-modify-write.
+            crate::with_cs(|cs| f(DEPTH.borrow(cs)))
+        }
+        #[cfg(not(target_arch = "avr"))]
+        {
+            DEPTH.with(f)
+        }
+    }
+
+    #[inline(always)]
+    pub(super) fn enter() {
+        #[cfg(debug_assertions)]
+        with_depth(|d| {
+            let (depth, max) = d.get();
+            let depth = depth.saturating_add(1);
+            d.set((depth, max.max(depth)));
+        });
+    }
+
+    #[inline(always)]
+    pub(super) fn leave() {
+        #[cfg(debug_assertions)]
+        with_depth(|d| {
+            let (depth, max) = d.get();
+            d.set((depth.saturating_sub(1), max));
+        });
+    }
+
+    #[inline(always)]
+    pub(super) fn depth() -> u8 {
+        #[cfg(debug_assertions)]
+        return with_depth(|d| d.get().0);
+        #[cfg(not(debug_assertions))]
+        0
+    }
+
+    #[inline(always)]
+    pub(super) fn max_observed() -> u8 {
+        #[cfg(debug_assertions)]
+        return with_depth(|d| d.get().1);
+        #[cfg(not(debug_assertions))]
+        0
+    }
+
+    #[inline(always)]
+    pub(super) fn reset_max_observed() {
+        #[cfg(debug_assertions)]
+        with_depth(|d| d.set((d.get().0, d.get().0)));
+    }
+}
 
 impl<'cs> MainCtx<'cs> {
     /// Create a new `main()` context.
@@ -104,10 +232,79 @@ impl<'cs> IrqCtx<'cs> {
     /// Interrupts must be disabled while calling this function.
     #[inline(always)]
     pub unsafe fn new() -> Self {
+        irq_nesting::enter();
         // SAFETY: The safety contract of the called function is equal to ours.
         unsafe { Self::internal_new() }
     }
 
+    /// Get the number of currently alive interrupt context markers.
+    ///
+    /// With nested interrupts this is the current interrupt nesting depth.
+    ///
+    /// This is only tracked in debug builds. It always returns 0 in release builds.
+    #[inline(always)]
+    pub fn nesting_depth() -> u8 {
+        irq_nesting::depth()
+    }
+
+    /// Get the maximum observed [Self::nesting_depth].
+    ///
+    /// This is useful to find the worst case interrupt nesting depth
+    /// when debugging stack usage.
+    ///
+    /// This is only tracked in debug builds. It always returns 0 in release builds.
+    /// On non-AVR hosts the depth is tracked per thread.
+    #[inline(always)]
+    pub fn max_observed() -> u8 {
+        irq_nesting::max_observed()
+    }
+
+    /// Reset the maximum observed nesting depth to the current nesting depth.
+    #[inline(always)]
+    pub fn reset_max_observed() {
+        irq_nesting::reset_max_observed();
+    }
+
+    /// Create a new interrupt context at the top of an `avr-hal` ISR.
+    ///
+    /// This is the integration point for interrupt service routines that are defined
+    /// with the `#[avr_device::interrupt]` attribute, as used by `avr-hal`,
+    /// instead of [crate::define_isr].
+    ///
+    /// This is only available with the `avr-hal` feature.
+    ///
+    /// # Safety
+    ///
+    /// This may only be called from the body of a function that
+    /// is a hardware interrupt service routine.
+    /// The interrupt service routine must not enable interrupts.
+    ///
+    /// The returned `IrqCtx` must not outlive the execution of the interrupt service routine.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use avr_context::IrqCtx;
+    ///
+    /// fn timer1_compa_isr(c: &IrqCtx<'_>) {
+    ///     // ...
+    /// }
+    ///
+    /// #[cfg_attr(target_arch = "avr", avr_device::interrupt(atmega328p))]
+    /// #[allow(non_snake_case)]
+    /// fn TIMER1_COMPA() {
+    ///     // SAFETY: This is the top of a hardware interrupt service routine.
+    ///     let c = unsafe { IrqCtx::in_isr() };
+    ///     timer1_compa_isr(&c);
+    /// }
+    /// ```
+    #[cfg(feature = "avr-hal")]
+    #[inline(always)]
+    pub unsafe fn in_isr() -> Self {
+        // SAFETY: The safety contract of the called function is part of ours.
+        unsafe { Self::new() }
+    }
+
     /// Get the `CriticalSection` that belongs to this context.
     ///
     /// In IRQ context, interrupts are disabled.
@@ -116,8 +313,235 @@ impl<'cs> IrqCtx<'cs> {
     pub fn cs(&self) -> CriticalSection<'cs> {
         self.0
     }
+
+    /// Replace the value in the shared `mutex` with `inner` and return the old value.
+    ///
+    /// This is useful for swapping a value that is shared between
+    /// interrupt context and main context, e.g. a "latest reading" slot.
+    #[inline(always)]
+    pub fn replace<T>(&self, mutex: &Mutex<Cell<T>>, inner: T) -> T {
+        mutex.borrow(self.cs()).replace(inner)
+    }
+
+    /// Increment the counter in `cell` by one.
+    ///
+    /// Returns `true`, if the counter wrapped around to zero.
+    /// This is useful for extending the width of hardware timer counters
+    /// in the overflow interrupt.
+    #[inline(always)]
+    pub fn bump(&self, cell: &IrqCtxCell<u16>) -> bool {
+        let count = cell.get(self).wrapping_add(1);
+        cell.set(self, count);
+        count == 0
+    }
+
+    /// Set the `bits` in `cell` and return the previous value.
+    ///
+    /// This is a plain read-modify-write, because interrupts are disabled.
+    /// It is useful for aggregating status bits in interrupt context.
+    #[inline(always)]
+    pub fn fetch_or(&self, cell: &IrqCtxCell<u8>, bits: u8) -> u8 {
+        let old = cell.get(self);
+        cell.set(self, old | bits);
+        old
+    }
+
+    /// Clear all bits in `cell` that are not set in `bits` and return the previous value.
+    ///
+    /// See [Self::fetch_or].
+    #[inline(always)]
+    pub fn fetch_and(&self, cell: &IrqCtxCell<u8>, bits: u8) -> u8 {
+        let old = cell.get(self);
+        cell.set(self, old & bits);
+        old
+    }
+
+    /// Get the current time of `clock` as the timestamp of the interrupt event.
+    ///
+    /// See [crate::time::TimestampSlot] for passing the timestamp to the main context.
+    #[inline(always)]
+    pub fn timestamp(&self, clock: &impl Clock) -> u32 {
+        clock.now()
+    }
+
+    /// Get the time of `clock` that has elapsed since the timestamp `since`.
+    ///
+    /// Wrap around of the clock is handled.
+    #[inline(always)]
+    pub fn elapsed_since(&self, clock: &impl Clock, since: u32) -> u32 {
+        self.timestamp(clock).wrapping_sub(since)
+    }
+}
+
+impl IrqCtx<'_> {
+    /// Convert this interrupt context marker into its FFI representation.
+    ///
+    /// See [FfiCtx].
+    #[inline(always)]
+    pub fn to_ffi(self) -> FfiCtx {
+        FfiCtx(core::ptr::null())
+    }
+
+    /// Convert the FFI representation back into an interrupt context marker.
+    ///
+    /// # Safety
+    ///
+    /// The safety contract of [IrqCtx::new] must be upheld.
+    ///
+    /// This may only be called from within the interrupt service routine
+    /// that the `ctx` was passed to.
+    /// The returned `IrqCtx` must not outlive the execution of that interrupt service routine.
+    #[inline(always)]
+    pub unsafe fn from_ffi(ctx: FfiCtx) -> Self {
+        let _ = ctx;
+        // SAFETY: The safety contract of the called function is equal to ours.
+        unsafe { Self::new() }
+    }
+}
+
+/// Tagged interrupt context marker.
+///
+/// Possession of this marker, or a reference to it,
+/// guarantees execution in the interrupt service routine that owns the `Tag`.
+///
+/// This is useful for systems with nested interrupts.
+/// If one interrupt service routine can preempt another one,
+/// data that belongs to one of them must not be accessed from the other one.
+/// A [crate::cell::TaggedIrqCtxCell] can only be accessed with
+/// the `TaggedIrqCtx` of the matching `Tag`.
+///
+/// The `Tag` is a user defined type without any runtime meaning.
+/// Usually it is an empty struct per interrupt service routine.
+///
+/// `TaggedIrqCtx` deliberately does not provide the untagged [IrqCtx] operations.
+/// Tagged interrupt service routines may be preempted by other interrupts.
+/// Therefore, interrupts are not necessarily disabled and untagged
+/// interrupt context data must not be accessed.
+///
+/// # Example
+///
+/// ```compile_fail
+/// use avr_context::{TaggedIrqCtx, cell::TaggedIrqCtxCell};
+///
+/// struct TimerTag;
+/// struct UartTag;
+///
+/// static TIMER_TICKS: TaggedIrqCtxCell<TimerTag, u16> = TaggedIrqCtxCell::new(0);
+///
+/// fn uart_isr(c: &TaggedIrqCtx<'_, UartTag>) {
+///     // This does not compile.
+///     // The UART ISR must not access data of the timer ISR.
+///     TIMER_TICKS.set(c, 42);
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use avr_context::{TaggedIrqCtx, cell::IrqCtxCell};
+///
+/// struct UartTag;
+///
+/// static SHARED: IrqCtxCell<u8> = IrqCtxCell::new(0);
+///
+/// fn uart_isr(c: &TaggedIrqCtx<'_, UartTag>) {
+///     // This does not compile.
+///     // Untagged interrupt data is not accessible from a tagged ISR.
+///     SHARED.set(c, 42);
+/// }
+/// ```
+pub struct TaggedIrqCtx<'cs, Tag> {
+    cs: CriticalSection<'cs>,
+    id: CtxId,
+    _tag: PhantomData<fn() -> Tag>,
 }
 
+impl<'cs, Tag> TaggedIrqCtx<'cs, Tag> {
+    /// Create a new tagged interrupt context.
+    ///
+    /// # Safety
+    ///
+    /// This constructor may only be called from the interrupt service routine that owns the `Tag`.
+    /// Interrupts may be enabled, so that other interrupts can preempt the routine.
+    ///
+    /// A `Tag` must only ever be used in one single interrupt service routine,
+    /// or in multiple interrupt service routines that cannot preempt each other.
+    #[inline(always)]
+    pub unsafe fn new() -> Self {
+        irq_nesting::enter();
+        // SAFETY:
+        // This `cs` is only used with `TaggedIrqCtxCell`s of our `Tag`.
+        // The `Tag` is owned by a single interrupt service routine
+        // and therefore these cells are never accessed concurrently.
+        let cs = unsafe { CriticalSection::new() };
+        // Barrier to ensure that no memory accesses from inside of the
+        // context are moved outside.
+        fence(SeqCst);
+        Self {
+            cs,
+            id: crate::with_cs(CtxId::next),
+            _tag: PhantomData,
+        }
+    }
+
+    /// Get the identity of this context marker instance.
+    ///
+    /// See [CtxId].
+    #[inline(always)]
+    pub fn id(&self) -> CtxId {
+        self.id
+    }
+
+    /// Get the `CriticalSection` that belongs to this context.
+    ///
+    /// Interrupts are not necessarily disabled.
+    /// Therefore, this critical section can only be used together with `TaggedIrqCtxCell`.
+    #[inline(always)]
+    pub(crate) fn cs(&self) -> CriticalSection<'cs> {
+        self.cs
+    }
+}
+
+impl<Tag> Drop for TaggedIrqCtx<'_, Tag> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        // Barrier to ensure that no memory accesses from inside of the
+        // context are moved outside.
+        fence(SeqCst);
+        irq_nesting::leave();
+    }
+}
+
+/// FFI representation of an interrupt context marker [IrqCtx].
+///
+/// This is useful for mixed C/Rust interrupt service routines,
+/// where a C interrupt service routine calls a Rust callback function.
+///
+/// The `IrqCtx` marker is a zero sized type in release builds.
+/// `FfiCtx` is a transparent wrapper around an opaque pointer (`void *`) instead.
+/// It is passed by value exactly like a `void *` argument.
+/// The pointer value carries no information. C code may pass `NULL`.
+///
+/// # Example
+///
+/// ```
+/// use avr_context::{FfiCtx, IrqCtx};
+///
+/// fn timer_callback(c: &IrqCtx<'_>) {
+///     // ...
+/// }
+///
+/// /// Rust callback that is called from the C interrupt service routine.
+/// ///
+/// /// C prototype: `void rust_timer_callback(void *ctx);`
+/// #[unsafe(no_mangle)]
+/// extern "C" fn rust_timer_callback(ctx: FfiCtx) {
+///     // SAFETY: This callback is only called from within the C interrupt service routine.
+///     let c = unsafe { IrqCtx::from_ffi(ctx) };
+///     timer_callback(&c);
+/// }
+/// ```
+#[repr(transparent)]
+pub struct FfiCtx(*const core::ffi::c_void);
+
 /// Main context initialization marker.
 ///
 /// This marker does not have a pub constructor.
@@ -152,6 +576,46 @@ impl<'cs> InitCtx<'cs> {
     pub fn main_ctx(&self) -> &'cs MainCtx<'cs> {
         self.0
     }
+
+    /// Temporarily enable interrupts while running `f`.
+    ///
+    /// Some initialization code needs interrupt driven drivers,
+    /// e.g. for reading configuration from an I2C EEPROM.
+    ///
+    /// `enable` is called to enable interrupts before `f` is called.
+    /// `disable` is called to disable interrupts after `f` returned.
+    /// This crate does not depend on a specific hardware abstraction,
+    /// therefore the enable/disable routines must be provided by the caller.
+    /// For example `avr_device::interrupt::enable` and `avr_device::interrupt::disable`.
+    ///
+    /// # Safety
+    ///
+    /// `enable` must enable interrupts and `disable` must disable interrupts.
+    ///
+    /// During `f` interrupts are enabled.
+    /// Therefore, the usual initialization context guarantees do not hold within `f`:
+    ///
+    /// - `f` must not use any `CriticalSection` obtained from this `InitCtx`.
+    /// - The interrupt service routines that run during `f` must not access
+    ///   any [InitCtxCell](crate::InitCtxCell) that is not fully initialized, yet.
+    /// - The interrupt service routines that run during `f` must not access
+    ///   any data that is accessed by the initialization code at the same time.
+    #[inline(always)]
+    pub unsafe fn with_interrupts<R>(
+        &self,
+        enable: impl FnOnce(),
+        disable: impl FnOnce(),
+        f: impl FnOnce() -> R,
+    ) -> R {
+        // Barrier to ensure that no memory accesses are moved into the interrupt window.
+        fence(SeqCst);
+        enable();
+        let ret = f();
+        disable();
+        // Barrier to ensure that no memory accesses are moved out of the interrupt window.
+        fence(SeqCst);
+        ret
+    }
 }
 
 impl<'cs> MainCtx<'cs> {
@@ -185,8 +649,249 @@ impl<'cs> MainCtx<'cs> {
 
         (main_ctx, ret)
     }
+
+    /// Assert that the initialization phase `P` has been completed.
+    ///
+    /// This function does nothing at runtime.
+    /// It only requires the caller to possess the [InitPhaseToken] of phase `P`.
+    #[inline(always)]
+    pub fn assert_init_done<P>(&self, _: &InitPhaseToken<P>) {}
+
+    /// Run `f` and check that it used at most `depth` bytes of stack.
+    ///
+    /// In debug builds on AVR a canary is painted `depth` bytes below the current
+    /// stack pointer before `f` runs and it is checked after `f` returned.
+    /// Interrupts that run during `f` also use the stack and count into `depth`.
+    ///
+    /// In release builds and on non-AVR hosts this just runs `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the canary has been overwritten,
+    /// or if `depth` is larger than the free stack.
+    /// The free stack ends at the end of the static data,
+    /// which is the `__heap_start` symbol of the avr-libc linker script.
+    #[inline(always)]
+    pub fn run_with_stack_guard<R>(&self, depth: usize, f: impl FnOnce() -> R) -> R {
+        #[cfg(all(debug_assertions, target_arch = "avr"))]
+        return crate::stackguard::guard(&crate::stackguard::AvrStack, depth, f);
+        #[cfg(not(all(debug_assertions, target_arch = "avr")))]
+        {
+            let _ = depth;
+            f()
+        }
+    }
+
+    /// Put the CPU to sleep by calling the user provided `enter` routine.
+    ///
+    /// `enter` typically executes the `sleep` instruction with interrupts enabled
+    /// and returns after an interrupt has woken up the CPU.
+    ///
+    /// Memory barriers are placed before and after `enter`.
+    /// That ensures that no memory accesses of the main context
+    /// are moved across the sleep/wake boundary.
+    #[inline(always)]
+    pub fn sleep(&self, enter: impl FnOnce()) {
+        // Barrier to ensure that no memory accesses are moved into the sleep.
+        fence(SeqCst);
+        enter();
+        // Barrier to ensure that no memory accesses are moved before the wake up.
+        fence(SeqCst);
+    }
+
+    /// Run a batch of related cell updates in `f`.
+    ///
+    /// Memory barriers are placed before and after `f`.
+    /// That ensures that all memory accesses of the batch are
+    /// completed before any subsequent code, such as [Self::sleep], runs.
+    ///
+    /// This does not disable interrupts.
+    /// Cells that are only accessible from `main()` context
+    /// are not concurrently accessed by interrupts anyway.
+    #[inline(always)]
+    pub fn batch<R>(&self, f: impl FnOnce(&Self) -> R) -> R {
+        // Barrier to ensure that no preceding memory accesses are moved into the batch.
+        fence(SeqCst);
+        let ret = f(self);
+        // Barrier to ensure that no memory accesses of the batch are moved after it.
+        fence(SeqCst);
+        ret
+    }
+
+    /// Take the first set flag of `flags`.
+    ///
+    /// Returns the index of the first flag in `flags` that is set and clears that flag.
+    /// The other flags are not modified.
+    /// Returns `None`, if no flag is set.
+    ///
+    /// All flags are checked in a single critical section.
+    /// This is useful for main loops that wait for multiple interrupt events.
+    #[inline(always)]
+    pub fn select_ready(&self, flags: &[&IrqFlag]) -> Option<usize> {
+        with_cs(|cs| {
+            let index = flags.iter().position(|flag| flag.is_set(cs))?;
+            flags[index].take_with_cs(cs);
+            Some(index)
+        })
+    }
+
+    /// Run the fallible operation `op` and retry it up to `max` times on error.
+    ///
+    /// Returns the first `Ok` result or the error of the last attempt.
+    #[inline(always)]
+    pub fn retry<T, E>(&self, max: u8, op: impl FnMut(&Self) -> Result<T, E>) -> Result<T, E> {
+        self.retry_with_backoff(max, |_| (), op)
+    }
+
+    /// Run the fallible operation `op` and retry it up to `max` times on error.
+    ///
+    /// `backoff` is called before each retry with the number of the retry, starting at 1.
+    /// It can be used to wait for an increasing time between the attempts,
+    /// e.g. with [Self::sleep] or with a busy delay loop.
+    ///
+    /// Returns the first `Ok` result or the error of the last attempt.
+    #[inline(always)]
+    pub fn retry_with_backoff<T, E>(
+        &self,
+        max: u8,
+        mut backoff: impl FnMut(u8),
+        mut op: impl FnMut(&Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut retry = 0;
+        loop {
+            match op(self) {
+                Err(_) if retry < max => {
+                    retry += 1;
+                    backoff(retry);
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Create a short lived [MainCtxCell] on the stack with the `init` value
+    /// and pass it to `f`.
+    ///
+    /// This is useful for local scratch state that is accessed with the
+    /// same context API as `static` cells, but that does not need to be `static`.
+    /// The cell is dropped after `f` returns.
+    #[inline(always)]
+    pub fn scoped_cell<T, R>(&self, init: T, f: impl FnOnce(&MainCtxCell<T>) -> R) -> R {
+        let cell = MainCtxCell::new(init);
+        f(&cell)
+    }
+}
+
+/// Proof that the initialization phase `P` has been completed.
+///
+/// The token can only be created from the [InitCtx] context
+/// and from code that is able to construct a value of the phase type `P`.
+/// Typically `P` is a type with a private field that is owned by the module
+/// that does the initialization.
+///
+/// Code that depends on the initialization of another module can require
+/// a reference to the token as an argument.
+/// That makes it a compile time error to call such code without the
+/// initialization having run.
+///
+/// # Example
+///
+/// ```
+/// use avr_context::{InitCtx, InitPhaseToken, MainCtx};
+///
+/// mod uart {
+///     use avr_context::{InitCtx, InitPhaseToken, MainCtx};
+///
+///     pub struct UartInit(());
+///
+///     pub fn init(c: &InitCtx<'_>) -> InitPhaseToken<UartInit> {
+///         // ... initialize the UART module ...
+///         InitPhaseToken::new(c, UartInit(()))
+///     }
+///
+///     pub fn send(m: &MainCtx<'_>, token: &InitPhaseToken<UartInit>, data: u8) {
+///         m.assert_init_done(token);
+///         // ...
+///     }
+/// }
+///
+/// fn init_function(c: &InitCtx<'_>, _: ()) -> InitPhaseToken<uart::UartInit> {
+///     uart::init(c)
+/// }
+///
+/// fn main_loop(m: &MainCtx<'_>, uart_token: InitPhaseToken<uart::UartInit>) {
+///     uart::send(m, &uart_token, 0x42);
+/// }
+/// ```
+///
+/// The token can't be obtained without running the initialization:
+///
+/// ```compile_fail
+/// use avr_context::{InitPhaseToken, MainCtx};
+///
+/// mod uart {
+///     pub struct UartInit(());
+/// }
+///
+/// fn main_loop(m: &MainCtx<'_>) {
+///     let token: InitPhaseToken<uart::UartInit> = InitPhaseToken(Default::default());
+///     m.assert_init_done(&token);
+/// }
+/// ```
+///
+/// Phase gated code can't be called without the token:
+///
+/// ```compile_fail
+/// use avr_context::{InitPhaseToken, MainCtx};
+///
+/// mod uart {
+///     use avr_context::{InitPhaseToken, MainCtx};
+///
+///     pub struct UartInit(());
+///
+///     pub fn send(m: &MainCtx<'_>, token: &InitPhaseToken<UartInit>, data: u8) {
+///         m.assert_init_done(token);
+///     }
+/// }
+///
+/// fn main_loop(m: &MainCtx<'_>) {
+///     uart::send(m, 0x42);
+/// }
+/// ```
+///
+/// The token can only be created in the initialization phase.
+/// The main loop can't create it, even if it can construct the phase type,
+/// because it has no [InitCtx]:
+///
+/// ```compile_fail
+/// use avr_context::{InitPhaseToken, MainCtx};
+///
+/// pub struct UartInit;
+///
+/// fn main_loop(m: &MainCtx<'_>) {
+///     let token = InitPhaseToken::new(m, UartInit);
+///     m.assert_init_done(&token);
+/// }
+/// ```
+pub struct InitPhaseToken<P>(PhantomData<fn() -> P>);
+
+impl<P> InitPhaseToken<P> {
+    /// Emit the token for the completed initialization phase `P`.
+    #[inline(always)]
+    pub fn new(_: &InitCtx<'_>, _phase: P) -> Self {
+        Self(PhantomData)
+    }
 }
 
+impl<P> Clone for InitPhaseToken<P> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for InitPhaseToken<P> {}
+
 /// Define a new `main()` loop together with the corresponding `InitCtx` and `MainCtx`.
 ///
 /// The init function can return a value of arbitrary type
@@ -367,8 +1072,56 @@ macro_rules! define_main {
 ///     isr: timer1_compa_isr,      // The interrupt service routine (ISR)
 /// }
 /// ```
+///
+/// The ISR can take the `IrqCtx` either as `&IrqCtx` or as `&mut IrqCtx`.
+/// The latter is needed for [IrqCtxCell::with_mut](crate::cell::IrqCtxCell::with_mut).
+///
+/// An optional `tag` passes a [TaggedIrqCtx] to the ISR instead.
+/// Each tag must only be used for one ISR.
+///
+/// ```
+/// use avr_context::{TaggedIrqCtx, define_isr};
+///
+/// struct Timer1Tag;
+///
+/// #[inline(always)]
+/// fn timer1_compa_isr(c: &TaggedIrqCtx<'_, Timer1Tag>) {
+///     // ...
+/// }
+///
+/// define_isr! {
+///     device: atmega328p,
+///     interrupt: TIMER1_COMPA,
+///     isr: timer1_compa_isr,
+///     tag: Timer1Tag,             // The tag of the ISR context
+/// }
+/// ```
 #[macro_export]
 macro_rules! define_isr {
+    (
+        device: $microcontroller:ident,
+        interrupt: $interrupt:ident,
+        isr: $isr:path,
+        tag: $tag:ty $(,)?
+    ) => {
+        #[cfg(target_arch = "avr")]
+        #[allow(non_snake_case)]
+        #[doc(hidden)]
+        mod $interrupt {
+            extern crate avr_device;
+            use super::*;
+
+            #[avr_device::interrupt($microcontroller)]
+            fn $interrupt() {
+                // SAFETY: We are executing in interrupt context.
+                // It is safe to construct `TaggedIrqCtx` here.
+                // The tag is only used for this ISR.
+                let mut c = unsafe { $crate::TaggedIrqCtx::<$tag>::new() };
+
+                $isr(&mut c);
+            }
+        }
+    };
     (
         device: $microcontroller:ident,
         interrupt: $interrupt:ident,
@@ -385,9 +1138,9 @@ macro_rules! define_isr {
             fn $interrupt() {
                 // SAFETY: We are executing in interrupt context.
                 // It is safe to construct `IrqCtx` here.
-                let c = unsafe { $crate::IrqCtx::new() };
+                let mut c = unsafe { $crate::IrqCtx::new() };
 
-                $isr(&c);
+                $isr(&mut c);
             }
         }
     };
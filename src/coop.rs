@@ -0,0 +1,176 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Cooperative mutual exclusion between tasks in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+use core::{
+    cell::UnsafeCell,
+    ops::{Deref, DerefMut},
+};
+
+/// Mutex for a resource that is shared between cooperative tasks in `main()` context.
+///
+/// All tasks run in `main()` context and they are never preempted by each other.
+/// However, a task can keep a resource across multiple scheduler runs,
+/// e.g. while a multi step bus transfer is in progress.
+/// This mutex prevents two tasks from both using the resource at the same time.
+///
+/// Locking never blocks. [Self::try_lock] fails, if the mutex is already locked.
+///
+/// The lock can optionally record the id of the owning task with [Self::try_lock_by].
+/// A scheduler can use [Self::owner] to raise the priority of the owner
+/// (priority inheritance). This crate does not implement that policy.
+pub struct CoopMutex<T> {
+    locked: MainCtxCell<bool>,
+    owner: MainCtxCell<Option<u8>>,
+    inner: UnsafeCell<T>,
+}
+
+/// Exclusive access to the resource of a locked [CoopMutex].
+///
+/// The mutex is unlocked when the guard is dropped.
+pub struct CoopMutexGuard<'a, T> {
+    mutex: &'a CoopMutex<T>,
+    m: &'a MainCtx<'a>,
+}
+
+impl<T> CoopMutex<T> {
+    /// Create a new unlocked mutex.
+    #[inline(always)]
+    pub const fn new(inner: T) -> Self {
+        Self {
+            locked: MainCtxCell::new(false),
+            owner: MainCtxCell::new(None),
+            inner: UnsafeCell::new(inner),
+        }
+    }
+
+    /// Try to lock the mutex.
+    ///
+    /// Returns `None`, if the mutex is already locked.
+    #[inline(always)]
+    pub fn try_lock<'a>(&'a self, m: &'a MainCtx<'a>) -> Option<CoopMutexGuard<'a, T>> {
+        if self.locked.replace(m, true) {
+            return None;
+        }
+        Some(CoopMutexGuard { mutex: self, m })
+    }
+
+    /// Try to lock the mutex on behalf of the task `owner`.
+    ///
+    /// Returns `None`, if the mutex is already locked.
+    #[inline(always)]
+    pub fn try_lock_by<'a>(
+        &'a self,
+        m: &'a MainCtx<'a>,
+        owner: u8,
+    ) -> Option<CoopMutexGuard<'a, T>> {
+        let guard = self.try_lock(m)?;
+        self.owner.set(m, Some(owner));
+        Some(guard)
+    }
+
+    /// Returns `true`, if the mutex is locked.
+    #[inline(always)]
+    pub fn is_locked(&self, m: &MainCtx<'_>) -> bool {
+        self.locked.get(m)
+    }
+
+    /// Get the id of the task that holds the lock.
+    ///
+    /// Returns `None`, if the mutex is not locked
+    /// or if it has been locked with [Self::try_lock].
+    #[inline(always)]
+    pub fn owner(&self, m: &MainCtx<'_>) -> Option<u8> {
+        self.owner.get(m)
+    }
+
+    /// Consume the mutex and return the inner value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+impl<T> Deref for CoopMutexGuard<'_, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        // SAFETY: The guard exists only once per mutex. Access is exclusive.
+        unsafe { &*self.mutex.inner.get() }
+    }
+}
+
+impl<T> DerefMut for CoopMutexGuard<'_, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: The guard exists only once per mutex. Access is exclusive.
+        unsafe { &mut *self.mutex.inner.get() }
+    }
+}
+
+impl<T> Drop for CoopMutexGuard<'_, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.mutex.owner.set(self.m, None);
+        self.mutex.locked.set(self.m, false);
+    }
+}
+
+impl<T: Default> Default for CoopMutex<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+// SAFETY: If T is Send, then we can Send the whole object.
+unsafe impl<T: Send> Send for CoopMutex<T> {}
+
+// SAFETY:
+// The inner value can only be accessed through a guard.
+// Locking requires a `MainCtx`, so the inner value is only accessed from `main()` context.
+unsafe impl<T: Send> Sync for CoopMutex<T> {}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_coop_mutex() {
+        let m = unsafe { MainCtx::new() };
+
+        let mutex = CoopMutex::new([0_u8; 4]);
+        assert!(!mutex.is_locked(&m));
+
+        {
+            let mut guard = mutex.try_lock(&m).unwrap();
+            guard[1] = 5;
+            assert!(mutex.is_locked(&m));
+            assert_eq!(mutex.owner(&m), None);
+            assert!(mutex.try_lock(&m).is_none());
+            assert!(mutex.try_lock_by(&m, 1).is_none());
+            assert_eq!(guard[1], 5);
+        }
+        assert!(!mutex.is_locked(&m));
+
+        {
+            let guard = mutex.try_lock_by(&m, 3).unwrap();
+            assert_eq!(mutex.owner(&m), Some(3));
+            assert!(mutex.try_lock(&m).is_none());
+            assert_eq!(mutex.owner(&m), Some(3));
+            drop(guard);
+        }
+        assert_eq!(mutex.owner(&m), None);
+
+        let guard = mutex.try_lock(&m).unwrap();
+        drop(guard);
+        assert_eq!(mutex.into_inner(), [0, 5, 0, 0]);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,112 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Input debouncing in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// Debounce filter for a polled input value that can only be accessed from `main()` context.
+///
+/// A new raw input value is accepted as the stable value,
+/// after it has been passed to [Self::update] in `N` consecutive calls.
+pub struct Debounce<T, const N: u8> {
+    candidate: MainCtxCell<T>,
+    stable: MainCtxCell<T>,
+    count: MainCtxCell<u8>,
+}
+
+impl<T: Copy, const N: u8> Debounce<T, N> {
+    /// Create a new debounce filter with the `initial` stable value.
+    #[inline(always)]
+    pub const fn new(initial: T) -> Self {
+        Self {
+            candidate: MainCtxCell::new(initial),
+            stable: MainCtxCell::new(initial),
+            count: MainCtxCell::new(0),
+        }
+    }
+
+    /// Get the current stable value.
+    #[inline(always)]
+    pub fn stable(&self, m: &MainCtx<'_>) -> T {
+        self.stable.get(m)
+    }
+}
+
+impl<T: Copy + PartialEq, const N: u8> Debounce<T, N> {
+    /// Feed the `raw` input value into the filter.
+    ///
+    /// Returns `Some` with the new stable value, if the stable value has changed.
+    /// Returns `None` otherwise.
+    #[inline(always)]
+    pub fn update(&self, m: &MainCtx<'_>, raw: T) -> Option<T> {
+        if raw == self.stable.get(m) {
+            self.count.set(m, 0);
+            return None;
+        }
+        let count = if self.count.get(m) > 0 && raw == self.candidate.get(m) {
+            self.count.get(m).saturating_add(1)
+        } else {
+            self.candidate.set(m, raw);
+            1
+        };
+        if count >= N {
+            self.stable.set(m, raw);
+            self.count.set(m, 0);
+            Some(raw)
+        } else {
+            self.count.set(m, count);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_debounce() {
+        let m = unsafe { MainCtx::new() };
+
+        let button: Debounce<bool, 3> = Debounce::new(false);
+        assert!(!button.stable(&m));
+
+        // Bouncing input.
+        assert_eq!(button.update(&m, true), None);
+        assert_eq!(button.update(&m, false), None);
+        assert_eq!(button.update(&m, true), None);
+        assert_eq!(button.update(&m, true), None);
+        assert_eq!(button.update(&m, false), None);
+        assert!(!button.stable(&m));
+
+        // Stable input.
+        assert_eq!(button.update(&m, true), None);
+        assert_eq!(button.update(&m, true), None);
+        assert_eq!(button.update(&m, true), Some(true));
+        assert_eq!(button.update(&m, true), None);
+        assert!(button.stable(&m));
+
+        assert_eq!(button.update(&m, false), None);
+        assert_eq!(button.update(&m, false), None);
+        assert_eq!(button.update(&m, false), Some(false));
+    }
+
+    #[test]
+    fn test_debounce_multi_value() {
+        let m = unsafe { MainCtx::new() };
+
+        let sel: Debounce<u8, 2> = Debounce::new(0);
+        assert_eq!(sel.update(&m, 1), None);
+        assert_eq!(sel.update(&m, 2), None);
+        assert_eq!(sel.update(&m, 2), Some(2));
+
+        let direct: Debounce<u8, 1> = Debounce::new(0);
+        assert_eq!(direct.update(&m, 5), Some(5));
+        assert_eq!(direct.update(&m, 5), None);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,114 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Decimation of high rate samples in interrupt context.
+
+use crate::{
+    Mutex,
+    cell::IrqCtxCell,
+    context::{IrqCtx, MainCtx},
+    with_cs,
+};
+use core::cell::Cell;
+
+/// Decimator that reduces the sample rate by the factor `R`.
+///
+/// The samples are pushed from interrupt context with [Self::push].
+/// Every `R`-th sample, the average of the last `R` samples
+/// is stored into a slot that the main loop reads with [Self::take].
+///
+/// `R` must be in the range `1..=65536`.
+pub struct Decimator<const R: usize> {
+    /// Sum and number of the accumulated samples.
+    acc: IrqCtxCell<(u32, usize)>,
+    output: Mutex<Cell<Option<u16>>>,
+}
+
+impl<const R: usize> Decimator<R> {
+    /// Create a new decimator without samples.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        const { assert!(R > 0 && R <= 0x1_0000) };
+        Self {
+            acc: IrqCtxCell::new((0, 0)),
+            output: Mutex::new(Cell::new(None)),
+        }
+    }
+
+    /// Push a new `sample` from interrupt context.
+    ///
+    /// Returns the decimated value, if this was the `R`-th sample.
+    /// A decimated value that has not been taken by `main()` is overwritten.
+    #[inline(always)]
+    pub fn push(&self, irq: &IrqCtx<'_>, sample: u16) -> Option<u16> {
+        let (sum, count) = self.acc.get(irq);
+        let (sum, count) = (sum + u32::from(sample), count + 1);
+        if count < R {
+            self.acc.set(irq, (sum, count));
+            return None;
+        }
+        self.acc.set(irq, (0, 0));
+        let average = (sum / R as u32) as u16;
+        self.output.borrow(irq.cs()).set(Some(average));
+        Some(average)
+    }
+
+    /// Take the latest decimated value from `main()` context.
+    ///
+    /// Returns `None`, if no new value is available since the last call.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn take(&self, _: &MainCtx<'_>) -> Option<u16> {
+        with_cs(|cs| self.output.borrow(cs).take())
+    }
+}
+
+impl<const R: usize> Default for Decimator<R> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decimator() {
+        let m = unsafe { MainCtx::new() };
+
+        let dec: Decimator<4> = Decimator::new();
+        assert_eq!(dec.take(&m), None);
+
+        let mut outputs = 0;
+        {
+            let irq = unsafe { IrqCtx::new() };
+            for i in 0..16_u16 {
+                if let Some(v) = dec.push(&irq, i * 10) {
+                    outputs += 1;
+                    assert_eq!(i % 4, 3);
+                    assert_eq!(v, (i - 3) * 10 + 15);
+                }
+            }
+            assert_eq!(outputs, 4);
+            assert_eq!(dec.push(&irq, 1), None);
+        }
+        assert_eq!(dec.take(&m), Some(135));
+        assert_eq!(dec.take(&m), None);
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert_eq!(dec.push(&irq, u16::MAX), None);
+            assert_eq!(dec.push(&irq, u16::MAX), None);
+            // (1 + 3 * 0xFFFF) / 4
+            assert_eq!(dec.push(&irq, u16::MAX), Some(49151));
+        }
+        assert_eq!(dec.take(&m), Some(49151));
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,89 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Deferred work from interrupt context to `main()` context.
+
+use crate::{
+    context::{IrqCtx, MainCtx},
+    spsc::SpscQueue,
+};
+
+/// Work items deferred from interrupt context to `main()` context.
+///
+/// An interrupt service routine can [defer](Self::defer) a small work token
+/// to be processed later in the main context by [run_pending](Self::run_pending).
+/// This is the classic "bottom half" pattern.
+///
+/// The hand-over is done with a lock-free [SpscQueue].
+/// It can hold up to `N - 1` pending work items.
+pub struct DeferredWork<const N: usize> {
+    queue: SpscQueue<u8, N>,
+}
+
+impl<const N: usize> DeferredWork<N> {
+    /// Create a new `DeferredWork` without pending work.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            queue: SpscQueue::new(),
+        }
+    }
+
+    /// Defer the work `work_id` from interrupt context.
+    ///
+    /// Returns `work_id` back, if there are too many pending work items.
+    #[inline(always)]
+    pub fn defer(&self, irq: &IrqCtx<'_>, work_id: u8) -> Result<(), u8> {
+        self.queue.enqueue(irq, work_id)
+    }
+
+    /// Run all pending work items in `main()` context.
+    ///
+    /// `dispatch` is called for each pending `work_id` in the order of deferral.
+    #[inline(always)]
+    pub fn run_pending(&self, m: &MainCtx<'_>, mut dispatch: impl FnMut(u8)) {
+        while let Some(work_id) = self.queue.dequeue(m) {
+            dispatch(work_id);
+        }
+    }
+}
+
+impl<const N: usize> Default for DeferredWork<N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    static WORK: DeferredWork<4> = DeferredWork::new();
+
+    #[test]
+    fn test_deferred_work() {
+        let m = unsafe { MainCtx::new() };
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert_eq!(WORK.defer(&irq, 7), Ok(()));
+            assert_eq!(WORK.defer(&irq, 3), Ok(()));
+        }
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert_eq!(WORK.defer(&irq, 5), Ok(()));
+            assert_eq!(WORK.defer(&irq, 9), Err(9));
+        }
+
+        let mut done = vec![];
+        WORK.run_pending(&m, |id| done.push(id));
+        assert_eq!(done, [7, 3, 5]);
+
+        WORK.run_pending(&m, |_| panic!("No work pending"));
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
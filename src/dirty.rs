@@ -0,0 +1,113 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Change tracking of values in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// A value with a dirty flag that can only be accessed from `main()` context.
+///
+/// Writing the value marks it dirty.
+/// A consumer, such as a render step, checks and clears the dirty flag
+/// with [Self::take_if_dirty] and only processes the value, if it has changed.
+pub struct Dirty<T> {
+    value: MainCtxCell<T>,
+    dirty: MainCtxCell<bool>,
+}
+
+impl<T> Dirty<T> {
+    /// Create a new value that is initially dirty.
+    ///
+    /// The consumer will see the initial value once.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self {
+            value: MainCtxCell::new(value),
+            dirty: MainCtxCell::new(true),
+        }
+    }
+
+    /// Create a new value that is initially clean.
+    #[inline(always)]
+    pub const fn new_clean(value: T) -> Self {
+        Self {
+            value: MainCtxCell::new(value),
+            dirty: MainCtxCell::new(false),
+        }
+    }
+
+    /// Returns `true`, if the value has been written since the last [Self::take_if_dirty].
+    #[inline(always)]
+    pub fn is_dirty(&self, m: &MainCtx<'_>) -> bool {
+        self.dirty.get(m)
+    }
+
+    /// Mark the value dirty without changing it.
+    #[inline(always)]
+    pub fn mark_dirty(&self, m: &MainCtx<'_>) {
+        self.dirty.set(m, true);
+    }
+}
+
+impl<T: Copy> Dirty<T> {
+    /// Get a copy of the value without changing the dirty flag.
+    #[inline(always)]
+    pub fn get(&self, m: &MainCtx<'_>) -> T {
+        self.value.get(m)
+    }
+
+    /// Set the value and mark it dirty.
+    #[inline(always)]
+    pub fn set(&self, m: &MainCtx<'_>, value: T) {
+        self.value.set(m, value);
+        self.dirty.set(m, true);
+    }
+
+    /// Get a copy of the value and clear the dirty flag, if the value is dirty.
+    ///
+    /// Returns `None`, if the value is clean.
+    #[inline(always)]
+    pub fn take_if_dirty(&self, m: &MainCtx<'_>) -> Option<T> {
+        if self.dirty.replace(m, false) {
+            Some(self.value.get(m))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dirty() {
+        let m = unsafe { MainCtx::new() };
+
+        let a = Dirty::new(1_u8);
+        assert!(a.is_dirty(&m));
+        assert_eq!(a.take_if_dirty(&m), Some(1));
+        assert!(!a.is_dirty(&m));
+        assert_eq!(a.take_if_dirty(&m), None);
+
+        a.set(&m, 2);
+        a.set(&m, 3);
+        assert!(a.is_dirty(&m));
+        assert_eq!(a.get(&m), 3);
+        assert!(a.is_dirty(&m));
+        assert_eq!(a.take_if_dirty(&m), Some(3));
+        assert_eq!(a.take_if_dirty(&m), None);
+
+        a.mark_dirty(&m);
+        assert_eq!(a.take_if_dirty(&m), Some(3));
+
+        let b = Dirty::new_clean(4_u8);
+        assert_eq!(b.take_if_dirty(&m), None);
+        b.set(&m, 4);
+        assert_eq!(b.take_if_dirty(&m), Some(4));
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,171 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Command dispatching in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+use core::marker::PhantomData;
+
+/// A command handler function that runs in `main()` context.
+///
+/// The second argument is the payload of the command.
+pub type Handler = fn(&MainCtx<'_>, &[u8]);
+
+/// Table of up to `N` command handlers in `main()` context.
+///
+/// `Cmd` is usually a fieldless command `enum`.
+/// Its conversion into `usize` is used as the index into the handler table.
+///
+/// The handlers are registered in the initialization phase with [Self::register].
+/// The main loop then passes parsed commands to [Self::dispatch].
+///
+/// # Example
+///
+/// ```
+/// use avr_context::{InitCtx, MainCtx, dispatch::Dispatcher};
+///
+/// #[derive(Clone, Copy)]
+/// enum Cmd {
+///     Ping,
+///     SetSpeed,
+/// }
+///
+/// impl From<Cmd> for usize {
+///     fn from(cmd: Cmd) -> usize {
+///         cmd as usize
+///     }
+/// }
+///
+/// static COMMANDS: Dispatcher<Cmd, 2> = Dispatcher::new();
+///
+/// fn set_speed(m: &MainCtx<'_>, payload: &[u8]) {
+///     // ...
+/// }
+///
+/// fn init(c: &InitCtx<'_>) {
+///     COMMANDS.register(c.main_ctx(), Cmd::SetSpeed, set_speed);
+/// }
+///
+/// fn handle(m: &MainCtx<'_>, cmd: Cmd, payload: &[u8]) {
+///     if COMMANDS.dispatch(m, cmd, payload).is_err() {
+///         // Unsupported command.
+///     }
+/// }
+/// ```
+pub struct Dispatcher<Cmd, const N: usize> {
+    handlers: [MainCtxCell<Option<Handler>>; N],
+    _cmd: PhantomData<fn(Cmd)>,
+}
+
+impl<Cmd: Copy + Into<usize>, const N: usize> Dispatcher<Cmd, N> {
+    /// Create a new dispatcher without handlers.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            handlers: [const { MainCtxCell::new(None) }; N],
+            _cmd: PhantomData,
+        }
+    }
+
+    /// Register the `handler` for `cmd`.
+    ///
+    /// A previously registered handler for `cmd` is replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the index of `cmd` is not smaller than `N`.
+    #[inline(always)]
+    pub fn register(&self, m: &MainCtx<'_>, cmd: Cmd, handler: Handler) {
+        self.handlers[cmd.into()].set(m, Some(handler));
+    }
+
+    /// Remove the handler for `cmd`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the index of `cmd` is not smaller than `N`.
+    #[inline(always)]
+    pub fn unregister(&self, m: &MainCtx<'_>, cmd: Cmd) {
+        self.handlers[cmd.into()].set(m, None);
+    }
+
+    /// Run the handler for `cmd` with the `payload`.
+    ///
+    /// Returns `cmd` back, if no handler is registered for it.
+    #[inline(always)]
+    pub fn dispatch(&self, m: &MainCtx<'_>, cmd: Cmd, payload: &[u8]) -> Result<(), Cmd> {
+        match self.handlers.get(cmd.into()).and_then(|h| h.get(m)) {
+            Some(handler) => {
+                handler(m, payload);
+                Ok(())
+            }
+            None => Err(cmd),
+        }
+    }
+}
+
+impl<Cmd: Copy + Into<usize>, const N: usize> Default for Dispatcher<Cmd, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Cmd {
+        Add,
+        Clear,
+        Unknown,
+        OutOfRange = 7,
+    }
+
+    impl From<Cmd> for usize {
+        fn from(cmd: Cmd) -> usize {
+            cmd as usize
+        }
+    }
+
+    static SUM: MainCtxCell<u32> = MainCtxCell::new(0);
+
+    fn add(m: &MainCtx<'_>, payload: &[u8]) {
+        let sum = payload.iter().map(|&b| u32::from(b)).sum::<u32>();
+        SUM.set(m, SUM.get(m) + sum);
+    }
+
+    fn clear(m: &MainCtx<'_>, _: &[u8]) {
+        SUM.set(m, 0);
+    }
+
+    #[test]
+    fn test_dispatcher() {
+        let m = unsafe { MainCtx::new() };
+
+        let disp: Dispatcher<Cmd, 3> = Dispatcher::new();
+        assert_eq!(disp.dispatch(&m, Cmd::Add, &[1]), Err(Cmd::Add));
+
+        disp.register(&m, Cmd::Add, add);
+        disp.register(&m, Cmd::Clear, clear);
+        assert_eq!(disp.dispatch(&m, Cmd::Add, &[1, 2, 3]), Ok(()));
+        assert_eq!(disp.dispatch(&m, Cmd::Add, &[4]), Ok(()));
+        assert_eq!(SUM.get(&m), 10);
+        assert_eq!(disp.dispatch(&m, Cmd::Unknown, &[]), Err(Cmd::Unknown));
+        assert_eq!(
+            disp.dispatch(&m, Cmd::OutOfRange, &[]),
+            Err(Cmd::OutOfRange)
+        );
+        assert_eq!(disp.dispatch(&m, Cmd::Clear, &[]), Ok(()));
+        assert_eq!(SUM.get(&m), 0);
+
+        disp.unregister(&m, Cmd::Add);
+        assert_eq!(disp.dispatch(&m, Cmd::Add, &[1]), Err(Cmd::Add));
+        assert_eq!(SUM.get(&m), 0);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,159 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Ping-pong buffers filled in interrupt context.
+
+use crate::{
+    Mutex,
+    context::{IrqCtx, MainCtx},
+    with_cs,
+};
+use core::cell::{Cell, UnsafeCell};
+
+/// Double buffer (ping-pong buffer) that is filled in interrupt context.
+///
+/// One buffer is in progress. It is being filled, e.g. by DMA or by an ADC interrupt.
+/// The other buffer holds the most recently completed data.
+/// The completion interrupt swaps the roles of the two buffers with [Self::swap_and_get_filled].
+/// The swap only exchanges the buffer index. No data is copied.
+///
+/// The main loop reads the filled buffer with [Self::read_filled].
+pub struct DoubleBuffer<T> {
+    bufs: [UnsafeCell<T>; 2],
+    in_progress: Mutex<Cell<u8>>,
+}
+
+impl<T> DoubleBuffer<T> {
+    /// Create a new double buffer.
+    ///
+    /// `a` is the initial in-progress buffer and `b` is the initial filled buffer.
+    #[inline(always)]
+    pub const fn new(a: T, b: T) -> Self {
+        Self {
+            bufs: [UnsafeCell::new(a), UnsafeCell::new(b)],
+            in_progress: Mutex::new(Cell::new(0)),
+        }
+    }
+
+    #[inline(always)]
+    fn buf(&self, index: u8) -> *mut T {
+        self.bufs[(index & 1) as usize].get()
+    }
+
+    /// Swap the in-progress buffer and the filled buffer from interrupt context.
+    ///
+    /// Returns a reference to the now filled buffer,
+    /// which was the in-progress buffer before the call.
+    #[inline(always)]
+    pub fn swap_and_get_filled<'a>(&'a self, irq: &'a IrqCtx<'_>) -> &'a T {
+        let in_progress = self.in_progress.borrow(irq.cs());
+        let filled = in_progress.get();
+        in_progress.set(filled ^ 1);
+        // SAFETY:
+        // The filled buffer is only written through `with_in_progress`,
+        // which needs `&mut IrqCtx` and therefore cannot be called while
+        // the returned reference that borrows `irq` is alive.
+        // Main context only reads it within a critical section.
+        unsafe { &*self.buf(filled) }
+    }
+
+    /// Modify the in-progress buffer with `f` from interrupt context.
+    ///
+    /// This requires `&mut IrqCtx` to prevent overlapping with references
+    /// obtained from [Self::swap_and_get_filled].
+    #[inline(always)]
+    pub fn with_in_progress<R>(&self, irq: &mut IrqCtx<'_>, f: impl FnOnce(&mut T) -> R) -> R {
+        let index = self.in_progress.borrow(irq.cs()).get();
+        // SAFETY:
+        // The in-progress buffer is never handed out to main context.
+        // Interrupts are disabled and `irq` is borrowed mutably,
+        // so there are no other references to it.
+        f(unsafe { &mut *self.buf(index) })
+    }
+
+    /// Get a raw pointer to the in-progress buffer from interrupt context.
+    ///
+    /// This can be used to program the DMA destination address.
+    /// The pointer is only valid for writing until the next swap.
+    #[inline(always)]
+    pub fn in_progress_ptr(&self, irq: &IrqCtx<'_>) -> *mut T {
+        self.buf(self.in_progress.borrow(irq.cs()).get())
+    }
+
+    /// Read the filled buffer with `f` from `main()` context.
+    ///
+    /// This enters a critical section while `f` runs.
+    /// Therefore, `f` should be short.
+    #[inline(always)]
+    pub fn read_filled<R>(&self, _: &MainCtx<'_>, f: impl FnOnce(&T) -> R) -> R {
+        with_cs(|cs| {
+            let filled = self.in_progress.borrow(cs).get() ^ 1;
+            // SAFETY:
+            // Interrupts are disabled. The buffers cannot be swapped while `f` runs.
+            // The filled buffer is not written.
+            f(unsafe { &*self.buf(filled) })
+        })
+    }
+}
+
+impl<T: Default> Default for DoubleBuffer<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default(), T::default())
+    }
+}
+
+// SAFETY: If T is Send, then we can Send the whole object.
+unsafe impl<T: Send> Send for DoubleBuffer<T> {}
+
+// SAFETY:
+// All accesses to the buffers and to the index are bound to critical sections
+// or to interrupt context. Critical sections never overlap in time.
+unsafe impl<T: Send> Sync for DoubleBuffer<T> {}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_double_buffer() {
+        let m = unsafe { MainCtx::new() };
+
+        let db: DoubleBuffer<[u16; 4]> = DoubleBuffer::default();
+        assert_eq!(db.read_filled(&m, |b| *b), [0; 4]);
+
+        for round in 1..=3 {
+            {
+                let mut irq = unsafe { IrqCtx::new() };
+                // Simulated ADC samples.
+                db.with_in_progress(&mut irq, |b| {
+                    for (i, s) in b.iter_mut().enumerate() {
+                        *s = round * 10 + i as u16;
+                    }
+                });
+                let filled = db.swap_and_get_filled(&irq);
+                assert_eq!(filled[0], round * 10);
+            }
+            let expected = [round * 10, round * 10 + 1, round * 10 + 2, round * 10 + 3];
+            assert_eq!(db.read_filled(&m, |b| *b), expected);
+        }
+    }
+
+    #[test]
+    fn test_double_buffer_no_copy() {
+        let m = unsafe { MainCtx::new() };
+
+        let db = DoubleBuffer::new([1_u8; 2], [2_u8; 2]);
+        let irq = unsafe { IrqCtx::new() };
+        let a = db.in_progress_ptr(&irq) as *const [u8; 2];
+        let filled = db.swap_and_get_filled(&irq) as *const [u8; 2];
+        assert_eq!(filled, a);
+        assert_ne!(db.in_progress_ptr(&irq) as *const [u8; 2], a);
+        drop(irq);
+        assert_eq!(db.read_filled(&m, |b| *b), [1; 2]);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
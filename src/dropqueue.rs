@@ -0,0 +1,134 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Deferred dropping of values from interrupt context in `main()` context.
+
+use crate::{
+    context::{IrqCtx, MainCtx},
+    spsc::SpscQueue,
+};
+
+/// Queue of values that are released in interrupt context
+/// and dropped later in `main()` context.
+///
+/// Running expensive destructors in an interrupt service routine
+/// increases the interrupt latency.
+/// The interrupt can move such values into this queue instead.
+/// The main loop then runs the destructors with [Self::process_drops].
+///
+/// The queue can hold up to `N - 1` values.
+/// `N` must be in the range `1..=256`.
+pub struct DropQueue<T, const N: usize> {
+    queue: SpscQueue<T, N>,
+}
+
+impl<T, const N: usize> DropQueue<T, N> {
+    /// Create a new empty drop queue.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            queue: SpscQueue::new(),
+        }
+    }
+
+    /// Enqueue `inner` for being dropped later in `main()` context.
+    ///
+    /// Returns `inner` back, if the queue is full.
+    #[inline(always)]
+    pub fn defer_drop(&self, irq: &IrqCtx<'_>, inner: T) -> Result<(), T> {
+        self.queue.enqueue(irq, inner)
+    }
+
+    /// Drop all queued values in `main()` context.
+    ///
+    /// Returns the number of dropped values.
+    #[inline(always)]
+    pub fn process_drops(&self, m: &MainCtx<'_>) -> usize {
+        let mut count = 0;
+        while let Some(inner) = self.queue.dequeue(m) {
+            drop(inner);
+            count += 1;
+        }
+        count
+    }
+
+    /// Get the number of queued values from `main()` context.
+    #[inline(always)]
+    pub fn len(&self, m: &MainCtx<'_>) -> usize {
+        self.queue.len(m)
+    }
+
+    /// Returns `true` if the queue is empty.
+    #[inline(always)]
+    pub fn is_empty(&self, m: &MainCtx<'_>) -> bool {
+        self.queue.is_empty(m)
+    }
+
+    /// Get the maximum number of values in the queue.
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+}
+
+impl<T, const N: usize> Default for DropQueue<T, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use core::cell::Cell;
+
+    struct DropCount<'a> {
+        drops: &'a Cell<u32>,
+        in_irq: &'a Cell<bool>,
+    }
+
+    impl Drop for DropCount<'_> {
+        fn drop(&mut self) {
+            assert!(!self.in_irq.get(), "dropped in interrupt context");
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_drop_queue() {
+        let m = unsafe { MainCtx::new() };
+
+        let drops = Cell::new(0);
+        let in_irq = Cell::new(false);
+        let new = || DropCount {
+            drops: &drops,
+            in_irq: &in_irq,
+        };
+
+        let q: DropQueue<DropCount<'_>, 3> = DropQueue::new();
+        assert_eq!(q.capacity(), 2);
+        assert_eq!(q.process_drops(&m), 0);
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            in_irq.set(true);
+            assert!(q.defer_drop(&irq, new()).is_ok());
+            assert!(q.defer_drop(&irq, new()).is_ok());
+            let full = q.defer_drop(&irq, new());
+            assert!(full.is_err());
+            in_irq.set(false);
+            drop(full);
+        }
+        assert_eq!(drops.get(), 1);
+        assert_eq!(q.len(&m), 2);
+
+        assert_eq!(q.process_drops(&m), 2);
+        assert_eq!(drops.get(), 3);
+        assert!(q.is_empty(&m));
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,125 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Edge detection of input levels in interrupt context.
+
+use crate::{
+    Mutex,
+    cell::IrqCtxCell,
+    context::{IrqCtx, MainCtx},
+    with_cs,
+};
+use core::cell::Cell;
+
+/// A level transition of a digital input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    /// Transition from low to high.
+    Rising,
+    /// Transition from high to low.
+    Falling,
+}
+
+/// Edge detector for a digital input, e.g. in a pin change interrupt.
+///
+/// The interrupt service routine passes the current input level to [Self::update].
+/// The most recent edge is also stored into a slot
+/// that the main loop reads with [Self::take_edge].
+pub struct EdgeDetect {
+    level: IrqCtxCell<bool>,
+    last_edge: Mutex<Cell<Option<Edge>>>,
+}
+
+impl EdgeDetect {
+    /// Create a new edge detector with the `initial` input level.
+    #[inline(always)]
+    pub const fn new(initial: bool) -> Self {
+        Self {
+            level: IrqCtxCell::new(initial),
+            last_edge: Mutex::new(Cell::new(None)),
+        }
+    }
+
+    /// Feed the current input `level` into the detector from interrupt context.
+    ///
+    /// Returns the detected edge or `None`, if the level did not change.
+    #[inline(always)]
+    pub fn update(&self, irq: &IrqCtx<'_>, level: bool) -> Option<Edge> {
+        let edge = match (self.level.replace(irq, level), level) {
+            (false, true) => Edge::Rising,
+            (true, false) => Edge::Falling,
+            _ => return None,
+        };
+        self.last_edge.borrow(irq.cs()).set(Some(edge));
+        Some(edge)
+    }
+
+    /// Get the last known input level from interrupt context.
+    #[inline(always)]
+    pub fn level(&self, irq: &IrqCtx<'_>) -> bool {
+        self.level.get(irq)
+    }
+
+    /// Take the most recent edge out of the slot from `main()` context.
+    ///
+    /// Returns `None`, if no edge has been detected since the last call.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn take_edge(&self, _: &MainCtx<'_>) -> Option<Edge> {
+        with_cs(|cs| self.last_edge.borrow(cs).take())
+    }
+
+    /// Get the most recent edge from `main()` context without removing it from the slot.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn peek_edge(&self, _: &MainCtx<'_>) -> Option<Edge> {
+        with_cs(|cs| self.last_edge.borrow(cs).get())
+    }
+}
+
+impl Default for EdgeDetect {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_edge_detect() {
+        let m = unsafe { MainCtx::new() };
+
+        let det = EdgeDetect::new(false);
+        assert_eq!(det.take_edge(&m), None);
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert_eq!(det.update(&irq, false), None);
+            assert_eq!(det.update(&irq, true), Some(Edge::Rising));
+            assert_eq!(det.update(&irq, true), None);
+            assert!(det.level(&irq));
+        }
+        assert_eq!(det.peek_edge(&m), Some(Edge::Rising));
+        assert_eq!(det.take_edge(&m), Some(Edge::Rising));
+        assert_eq!(det.take_edge(&m), None);
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert_eq!(det.update(&irq, false), Some(Edge::Falling));
+            assert_eq!(det.update(&irq, true), Some(Edge::Rising));
+            assert_eq!(det.update(&irq, false), Some(Edge::Falling));
+            assert_eq!(det.update(&irq, false), None);
+        }
+        // Only the most recent edge is kept.
+        assert_eq!(det.take_edge(&m), Some(Edge::Falling));
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,169 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Non-panicking error accumulation.
+
+use crate::{
+    Mutex,
+    cell::MainCtxCell,
+    context::{IrqCtx, MainCtx},
+    with_cs,
+};
+use core::cell::Cell;
+
+/// Accumulator for non-fatal error codes.
+///
+/// Each error code in the range `0..16` is one bit in a 16 bit bitfield.
+/// Errors can be recorded from `main()` context and from interrupt context.
+/// The main loop takes all accumulated errors,
+/// e.g. once per loop iteration, for later reporting.
+///
+/// Errors recorded from `main()` context do not disable interrupts.
+/// Errors recorded from interrupt context are stored in a separate
+/// bitfield that is protected by a critical section.
+pub struct ErrorSink {
+    main: MainCtxCell<u16>,
+    irq: Mutex<Cell<u16>>,
+}
+
+impl ErrorSink {
+    /// Create a new error sink without any recorded errors.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            main: MainCtxCell::new(0),
+            irq: Mutex::new(Cell::new(0)),
+        }
+    }
+
+    #[inline(always)]
+    fn bit(code: u8) -> u16 {
+        debug_assert!(code < 16, "ErrorSink: error code out of range");
+        1_u16.checked_shl(code.into()).unwrap_or(0)
+    }
+
+    /// Record the error `code` from `main()` context.
+    ///
+    /// `code` must be smaller than 16.
+    /// Larger codes are ignored in release builds.
+    #[inline(always)]
+    pub fn record(&self, m: &MainCtx<'_>, code: u8) {
+        self.main.set(m, self.main.get(m) | Self::bit(code));
+    }
+
+    /// Record the error `code` from interrupt context.
+    ///
+    /// `code` must be smaller than 16.
+    /// Larger codes are ignored in release builds.
+    #[inline(always)]
+    pub fn record_irq(&self, irq: &IrqCtx<'_>, code: u8) {
+        let errors = self.irq.borrow(irq.cs());
+        errors.set(errors.get() | Self::bit(code));
+    }
+
+    /// Take all recorded errors from `main()` and interrupt context
+    /// and reset the accumulator.
+    ///
+    /// Bit `n` of the returned value is set, if error code `n` has been recorded.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn take_errors(&self, m: &MainCtx<'_>) -> u16 {
+        let irq_errors = with_cs(|cs| self.irq.borrow(cs).replace(0));
+        self.main.replace(m, 0) | irq_errors
+    }
+}
+
+/// Check an invariant in `main()` context without panicking.
+///
+/// `soft_assert!(m, cond, sink, code)` evaluates the boolean expression `cond`.
+/// If it is `false`, then the error `code` is recorded into the [ErrorSink] `sink`
+/// with the `main()` context `m`.
+/// The program continues running in either case.
+///
+/// The macro evaluates to the value of `cond`.
+///
+/// # Example
+///
+/// ```
+/// use avr_context::{MainCtx, errors::ErrorSink, soft_assert};
+///
+/// static ERRORS: ErrorSink = ErrorSink::new();
+/// const ERR_SPEED_RANGE: u8 = 2;
+///
+/// fn set_speed(m: &MainCtx<'_>, speed: u16) {
+///     if !soft_assert!(m, speed <= 1000, ERRORS, ERR_SPEED_RANGE) {
+///         return;
+///     }
+///     // ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! soft_assert {
+    ($m:expr, $cond:expr, $sink:expr, $code:expr $(,)?) => {{
+        let ok: bool = $cond;
+        if !ok {
+            $crate::errors::ErrorSink::record(&$sink, $m, $code);
+        }
+        ok
+    }};
+}
+
+impl Default for ErrorSink {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_error_sink() {
+        let m = unsafe { MainCtx::new() };
+
+        let errors = ErrorSink::new();
+        assert_eq!(errors.take_errors(&m), 0);
+
+        errors.record(&m, 0);
+        errors.record(&m, 3);
+        errors.record(&m, 3);
+        {
+            let irq = unsafe { IrqCtx::new() };
+            errors.record_irq(&irq, 15);
+            errors.record_irq(&irq, 0);
+        }
+        assert_eq!(errors.take_errors(&m), 0x8009);
+        assert_eq!(errors.take_errors(&m), 0);
+
+        errors.record(&m, 1);
+        assert_eq!(errors.take_errors(&m), 0x0002);
+    }
+
+    #[test]
+    fn test_soft_assert() {
+        let m = unsafe { MainCtx::new() };
+
+        let errors = ErrorSink::new();
+        let mut reached = 0;
+
+        assert!(soft_assert!(&m, 1 + 1 == 2, errors, 4));
+        reached += 1;
+        assert_eq!(errors.take_errors(&m), 0);
+
+        let value = 7;
+        assert!(!soft_assert!(&m, value < 5, errors, 4));
+        reached += 1;
+        assert!(!soft_assert!(&m, value == 0, &errors, 9,));
+        reached += 1;
+
+        assert_eq!(reached, 3);
+        assert_eq!(errors.take_errors(&m), 0x0210);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,287 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Event flags between interrupt context and `main()` context.
+
+use crate::{
+    CriticalSection, Mutex,
+    context::{IrqCtx, MainCtx},
+    with_cs,
+};
+use core::{
+    cell::Cell,
+    sync::atomic::{
+        AtomicBool,
+        Ordering::{Relaxed, SeqCst},
+        compiler_fence,
+    },
+};
+
+/// An event flag that is set from interrupt context
+/// and taken from `main()` context.
+///
+/// The flag is protected by a critical section.
+pub struct IrqFlag {
+    flag: Mutex<Cell<bool>>,
+}
+
+impl IrqFlag {
+    /// Create a new flag that is not set.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            flag: Mutex::new(Cell::new(false)),
+        }
+    }
+
+    /// Set the flag from interrupt context.
+    #[inline(always)]
+    pub fn set(&self, irq: &IrqCtx<'_>) {
+        self.set_with_cs(irq.cs());
+    }
+
+    /// Set the flag with the given critical section.
+    #[inline(always)]
+    pub fn set_with_cs(&self, cs: CriticalSection<'_>) {
+        self.flag.borrow(cs).set(true);
+    }
+
+    /// Returns `true`, if the flag is set.
+    #[inline(always)]
+    pub fn is_set(&self, cs: CriticalSection<'_>) -> bool {
+        self.flag.borrow(cs).get()
+    }
+
+    /// Clear the flag with the given critical section and return the previous state.
+    #[inline(always)]
+    pub fn take_with_cs(&self, cs: CriticalSection<'_>) -> bool {
+        self.flag.borrow(cs).replace(false)
+    }
+
+    /// Clear the flag from `main()` context and return the previous state.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn take(&self, _: &MainCtx<'_>) -> bool {
+        with_cs(|cs| self.take_with_cs(cs))
+    }
+}
+
+impl Default for IrqFlag {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A one-shot latch that is armed from `main()` context
+/// and consumed from interrupt context.
+///
+/// This is useful for actions that the next interrupt shall run exactly once.
+/// The latch is protected by a critical section.
+pub struct OneShot {
+    armed: Mutex<Cell<bool>>,
+}
+
+impl OneShot {
+    /// Create a new latch that is not armed.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            armed: Mutex::new(Cell::new(false)),
+        }
+    }
+
+    /// Arm the latch from `main()` context.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn arm(&self, _: &MainCtx<'_>) {
+        with_cs(|cs| self.arm_with_cs(cs));
+    }
+
+    /// Arm the latch with the given critical section.
+    #[inline(always)]
+    pub fn arm_with_cs(&self, cs: CriticalSection<'_>) {
+        self.armed.borrow(cs).set(true);
+    }
+
+    /// Disarm the latch from `main()` context and return the previous state.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn disarm(&self, _: &MainCtx<'_>) -> bool {
+        with_cs(|cs| self.armed.borrow(cs).replace(false))
+    }
+
+    /// Returns `true`, if the latch is armed.
+    #[inline(always)]
+    pub fn is_armed(&self, cs: CriticalSection<'_>) -> bool {
+        self.armed.borrow(cs).get()
+    }
+
+    /// Check and consume the latch from interrupt context.
+    ///
+    /// Returns `true` once after the latch has been armed.
+    /// The latch is disarmed afterwards.
+    #[inline(always)]
+    pub fn triggered(&self, irq: &IrqCtx<'_>) -> bool {
+        self.armed.borrow(irq.cs()).replace(false)
+    }
+}
+
+impl Default for OneShot {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An event flag that is set from interrupt context
+/// and read and cleared from `main()` context without a critical section.
+///
+/// This is the cheapest way to signal an event from an interrupt to `main()`.
+/// Neither side disables interrupts.
+///
+/// # AVR atomicity
+///
+/// The flag is a single byte.
+/// On AVR a single byte load or store is one instruction and therefore atomic
+/// with respect to interrupts.
+/// This is only true for single bytes (`u8`).
+/// Multi byte values can be torn by an interrupt and are *not* suitable for this scheme.
+///
+/// The flag uses only byte loads and byte stores plus [compiler_fence]s.
+/// There is no atomic read-modify-write.
+/// Therefore, a [Self::set] that happens between the read and the clear of
+/// [Self::read_and_clear] is merged with the event that is just being read.
+/// Handle the event *after* [Self::read_and_clear] returned and re-read the
+/// shared state there. Then no event is lost.
+///
+/// # Memory ordering
+///
+/// The loads and stores are `Relaxed`.
+/// The [compiler_fence]s only keep the compiler from moving memory accesses
+/// across the flag accesses. They don't emit hardware barriers.
+/// This is sufficient on a single core, like AVR, where the interrupt
+/// and `main()` run on the same CPU and see its memory accesses in program order.
+/// It does not order memory accesses between multiple CPU cores.
+pub struct ByteFlag {
+    flag: AtomicBool,
+}
+
+impl ByteFlag {
+    /// Create a new flag that is not set.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            flag: AtomicBool::new(false),
+        }
+    }
+
+    /// Set the flag from interrupt context.
+    ///
+    /// On a single core all memory writes before this call are visible to `main()`
+    /// after it has seen the flag. See [ByteFlag] for the memory ordering.
+    #[inline(always)]
+    pub fn set(&self, _: &IrqCtx<'_>) {
+        compiler_fence(SeqCst);
+        self.flag.store(true, Relaxed);
+    }
+
+    /// Read and clear the flag from `main()` context and return the previous state.
+    ///
+    /// This does not enter a critical section.
+    #[inline(always)]
+    pub fn read_and_clear(&self, _: &MainCtx<'_>) -> bool {
+        let set = self.flag.load(Relaxed);
+        if set {
+            self.flag.store(false, Relaxed);
+        }
+        compiler_fence(SeqCst);
+        set
+    }
+}
+
+impl Default for ByteFlag {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// On AVR only byte sized loads and stores are atomic.
+#[cfg(target_arch = "avr")]
+const _: () = assert!(core::mem::size_of::<ByteFlag>() == 1);
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_irq_flag() {
+        let m = unsafe { MainCtx::new() };
+
+        let flag = IrqFlag::new();
+        assert!(!flag.take(&m));
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert!(!flag.is_set(irq.cs()));
+            flag.set(&irq);
+            flag.set(&irq);
+            assert!(flag.is_set(irq.cs()));
+        }
+        assert!(flag.take(&m));
+        assert!(!flag.take(&m));
+    }
+
+    #[test]
+    fn test_one_shot() {
+        let m = unsafe { MainCtx::new() };
+
+        let latch = OneShot::new();
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert!(!latch.triggered(&irq));
+        }
+
+        latch.arm(&m);
+        latch.arm(&m);
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert!(latch.is_armed(irq.cs()));
+            assert!(latch.triggered(&irq));
+            assert!(!latch.triggered(&irq));
+            assert!(!latch.is_armed(irq.cs()));
+        }
+
+        latch.arm(&m);
+        assert!(latch.disarm(&m));
+        assert!(!latch.disarm(&m));
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert!(!latch.triggered(&irq));
+        }
+    }
+
+    #[test]
+    fn test_byte_flag() {
+        let m = unsafe { MainCtx::new() };
+
+        assert_eq!(core::mem::size_of::<ByteFlag>(), 1);
+
+        let flag = ByteFlag::new();
+        assert!(!flag.read_and_clear(&m));
+        {
+            let irq = unsafe { IrqCtx::new() };
+            flag.set(&irq);
+            flag.set(&irq);
+        }
+        assert!(flag.read_and_clear(&m));
+        assert!(!flag.read_and_clear(&m));
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,178 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Assembly of delimited frames from byte streams in interrupt context.
+
+use crate::{
+    Mutex,
+    cell::IrqCtxCell,
+    context::{IrqCtx, MainCtx},
+    spsc::SpscQueue,
+    with_cs,
+};
+use core::cell::Cell;
+
+/// A received frame of up to `N` bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Frame<const N: usize> {
+    data: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Frame<N> {
+    /// Get the bytes of the frame without the delimiter.
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    /// Get the length of the frame in bytes.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true`, if the frame has no bytes.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Assembler for delimiter terminated frames of up to `N` bytes.
+///
+/// The receive interrupt, e.g. of a UART, passes each received byte to [Self::push_byte].
+/// When the delimiter byte is received, the frame is complete
+/// and it is enqueued into a queue of up to `Q - 1` frames.
+/// The main loop dequeues the frames with [Self::take_frame].
+///
+/// Frames that are longer than `N` bytes
+/// and frames that don't fit into the full queue are dropped.
+/// Empty frames are ignored.
+pub struct FrameAssembler<const N: usize, const Q: usize> {
+    delimiter: u8,
+    buf: Mutex<[Cell<u8>; N]>,
+    /// Length of the frame in progress and overflow state.
+    state: IrqCtxCell<(usize, bool)>,
+    frames: SpscQueue<Frame<N>, Q>,
+    dropped: Mutex<Cell<u16>>,
+}
+
+impl<const N: usize, const Q: usize> FrameAssembler<N, Q> {
+    /// Create a new frame assembler for frames terminated by `delimiter`.
+    #[inline(always)]
+    pub const fn new(delimiter: u8) -> Self {
+        Self {
+            delimiter,
+            buf: Mutex::new([const { Cell::new(0) }; N]),
+            state: IrqCtxCell::new((0, false)),
+            frames: SpscQueue::new(),
+            dropped: Mutex::new(Cell::new(0)),
+        }
+    }
+
+    /// Push a received byte `b` from interrupt context.
+    ///
+    /// Returns the length of the frame, if `b` completed a frame and it has been queued.
+    #[inline(always)]
+    pub fn push_byte(&self, irq: &IrqCtx<'_>, b: u8) -> Option<usize> {
+        let (len, overflow) = self.state.get(irq);
+        if b != self.delimiter {
+            if len < N {
+                self.buf.borrow(irq.cs())[len].set(b);
+                self.state.set(irq, (len + 1, overflow));
+            } else {
+                self.state.set(irq, (len, true));
+            }
+            return None;
+        }
+
+        self.state.set(irq, (0, false));
+        if len == 0 && !overflow {
+            return None;
+        }
+        if !overflow {
+            let mut frame = Frame { data: [0; N], len };
+            for (d, s) in frame.data.iter_mut().zip(self.buf.borrow(irq.cs())) {
+                *d = s.get();
+            }
+            if self.frames.enqueue(irq, frame).is_ok() {
+                return Some(len);
+            }
+        }
+        let dropped = self.dropped.borrow(irq.cs());
+        dropped.set(dropped.get().saturating_add(1));
+        None
+    }
+
+    /// Take the oldest completed frame from `main()` context.
+    #[inline(always)]
+    pub fn take_frame(&self, m: &MainCtx<'_>) -> Option<Frame<N>> {
+        self.frames.dequeue(m)
+    }
+
+    /// Get and reset the number of dropped frames from `main()` context.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn take_dropped(&self, _: &MainCtx<'_>) -> u16 {
+        with_cs(|cs| self.dropped.borrow(cs).take())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    fn feed<const N: usize, const Q: usize>(fa: &FrameAssembler<N, Q>, bytes: &[u8]) -> usize {
+        let irq = unsafe { IrqCtx::new() };
+        bytes.iter().filter_map(|&b| fa.push_byte(&irq, b)).count()
+    }
+
+    #[test]
+    fn test_frame_assembler() {
+        let m = unsafe { MainCtx::new() };
+
+        let fa: FrameAssembler<4, 4> = FrameAssembler::new(b'\n');
+        assert_eq!(fa.take_frame(&m), None);
+
+        assert_eq!(feed(&fa, b"ab\n\n\ncd"), 1);
+        let frame = fa.take_frame(&m).unwrap();
+        assert_eq!(frame.as_bytes(), b"ab");
+        assert_eq!(frame.len(), 2);
+        assert_eq!(fa.take_frame(&m), None);
+
+        // The frame in progress continues.
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert_eq!(fa.push_byte(&irq, b'e'), None);
+            assert_eq!(fa.push_byte(&irq, b'\n'), Some(3));
+        }
+        assert_eq!(fa.take_frame(&m).unwrap().as_bytes(), b"cde");
+        assert_eq!(fa.take_dropped(&m), 0);
+    }
+
+    #[test]
+    fn test_frame_assembler_drops() {
+        let m = unsafe { MainCtx::new() };
+
+        let fa: FrameAssembler<4, 3> = FrameAssembler::new(0);
+
+        // Too long.
+        assert_eq!(feed(&fa, b"12345\0abcd\0"), 1);
+        assert_eq!(fa.take_dropped(&m), 1);
+        assert_eq!(fa.take_frame(&m).unwrap().as_bytes(), b"abcd");
+
+        // Queue full.
+        assert_eq!(feed(&fa, b"a\0b\0c\0"), 2);
+        assert_eq!(fa.take_dropped(&m), 1);
+        assert_eq!(fa.take_dropped(&m), 0);
+        assert_eq!(fa.take_frame(&m).unwrap().as_bytes(), b"a");
+        assert_eq!(fa.take_frame(&m).unwrap().as_bytes(), b"b");
+        assert_eq!(fa.take_frame(&m), None);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
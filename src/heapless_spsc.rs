@@ -0,0 +1,151 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Context gated `heapless::spsc` queue endpoints.
+//!
+//! The [IrqCtxProducer] can only be used from interrupt context
+//! and the [MainCtxConsumer] can only be used from `main()` context.
+//! Both endpoints are stored in [InitCtxCell]s and must be initialized
+//! with the halves of a split `heapless::spsc::Queue` during construction of the [MainCtx].
+//!
+//! This module is only available with the `heapless` feature.
+
+use crate::{
+    cell::InitCtxCell,
+    context::{InitCtx, IrqCtx, MainCtx},
+};
+use heapless::spsc::{Consumer, Producer};
+
+/// The consumer endpoint of a `heapless::spsc::Queue`
+/// that can only be accessed from `main()` context.
+#[repr(transparent)]
+pub struct MainCtxConsumer<T: 'static>(InitCtxCell<Consumer<'static, T>>);
+
+impl<T> MainCtxConsumer<T> {
+    /// Get an uninitialized instance of [MainCtxConsumer].
+    ///
+    /// # Safety
+    ///
+    /// The safety contract of [InitCtxCell::uninit] must be upheld.
+    /// The returned instance must be initialized with a call to [Self::init].
+    #[inline(always)]
+    pub const unsafe fn uninit() -> Self {
+        // SAFETY: The safety contract of the called function is equal to ours.
+        Self(unsafe { InitCtxCell::uninit() })
+    }
+
+    /// Initialize the endpoint with the `consumer` half of a split queue.
+    ///
+    /// This must be called *once* during construction of the [MainCtx].
+    #[inline(always)]
+    pub fn init(&self, c: &InitCtx<'_>, consumer: Consumer<'static, T>) {
+        self.0.init(c, consumer);
+    }
+
+    /// Dequeue an item from the queue from a main context `MainCtx`.
+    #[inline(always)]
+    pub fn dequeue(&self, _: &MainCtx<'_>) -> Option<T> {
+        // SAFETY:
+        // The [Self::uninit] safety contract ensures that the cell is initialized.
+        // The consumer is only ever accessed from the main context.
+        // The main context does not run concurrently with itself
+        // and `dequeue` does not call back into user code.
+        // Therefore, this is the only reference to the consumer.
+        unsafe { (*self.0.inner_ptr()).dequeue() }
+    }
+
+    /// Get the number of items in the queue from a main context `MainCtx`.
+    #[inline(always)]
+    pub fn len(&self, _: &MainCtx<'_>) -> usize {
+        // SAFETY: See [Self::dequeue].
+        unsafe { (*self.0.inner_ptr()).len() }
+    }
+
+    /// Returns `true` if the queue is empty.
+    #[inline(always)]
+    pub fn is_empty(&self, m: &MainCtx<'_>) -> bool {
+        self.len(m) == 0
+    }
+}
+
+/// The producer endpoint of a `heapless::spsc::Queue`
+/// that can only be accessed from interrupt context.
+#[repr(transparent)]
+pub struct IrqCtxProducer<T: 'static>(InitCtxCell<Producer<'static, T>>);
+
+impl<T> IrqCtxProducer<T> {
+    /// Get an uninitialized instance of [IrqCtxProducer].
+    ///
+    /// # Safety
+    ///
+    /// The safety contract of [InitCtxCell::uninit] must be upheld.
+    /// The returned instance must be initialized with a call to [Self::init].
+    #[inline(always)]
+    pub const unsafe fn uninit() -> Self {
+        // SAFETY: The safety contract of the called function is equal to ours.
+        Self(unsafe { InitCtxCell::uninit() })
+    }
+
+    /// Initialize the endpoint with the `producer` half of a split queue.
+    ///
+    /// This must be called *once* during construction of the [MainCtx].
+    #[inline(always)]
+    pub fn init(&self, c: &InitCtx<'_>, producer: Producer<'static, T>) {
+        self.0.init(c, producer);
+    }
+
+    /// Enqueue an item into the queue from an interrupt context `IrqCtx`.
+    ///
+    /// Returns the item back, if the queue is full.
+    #[inline(always)]
+    pub fn enqueue(&self, _: &IrqCtx<'_>, item: T) -> Result<(), T> {
+        // SAFETY:
+        // The [Self::uninit] safety contract ensures that the cell is initialized.
+        // The producer is only ever accessed from interrupt context
+        // with interrupts disabled and `enqueue` does not call back into user code.
+        // Therefore, this is the only reference to the producer.
+        unsafe { (*self.0.inner_ptr()).enqueue(item) }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use heapless::spsc::Queue;
+
+    static CONSUMER: MainCtxConsumer<u16> = unsafe { MainCtxConsumer::uninit() };
+    static PRODUCER: IrqCtxProducer<u16> = unsafe { IrqCtxProducer::uninit() };
+
+    #[test]
+    fn test_heapless_spsc() {
+        fn init(c: &InitCtx<'_>, queue: &'static mut Queue<u16, 4>) {
+            let (p, c_) = queue.split();
+            PRODUCER.init(c, p);
+            CONSUMER.init(c, c_);
+        }
+        let queue = Box::leak(Box::new(Queue::new()));
+        let (m, ()) = unsafe { MainCtx::new_with_init(init, queue) };
+
+        assert!(CONSUMER.is_empty(&m));
+        assert_eq!(CONSUMER.dequeue(&m), None);
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert_eq!(PRODUCER.enqueue(&irq, 1), Ok(()));
+            assert_eq!(PRODUCER.enqueue(&irq, 2), Ok(()));
+            assert_eq!(PRODUCER.enqueue(&irq, 3), Ok(()));
+            assert_eq!(PRODUCER.enqueue(&irq, 4), Err(4));
+        }
+
+        assert_eq!(CONSUMER.len(&m), 3);
+        assert_eq!(CONSUMER.dequeue(&m), Some(1));
+        assert_eq!(CONSUMER.dequeue(&m), Some(2));
+        assert_eq!(CONSUMER.dequeue(&m), Some(3));
+        assert_eq!(CONSUMER.dequeue(&m), None);
+        assert!(CONSUMER.is_empty(&m));
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,108 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Histograms for runtime profiling in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// Histogram of values with `B` buckets of equal width
+/// that can only be accessed from `main()` context.
+///
+/// Bucket `n` counts the values in the range `n * width..(n + 1) * width`.
+/// The last bucket also counts all bigger values.
+/// The counters saturate at `u16::MAX`.
+///
+/// This is useful for profiling the distribution of e.g. main loop iteration times.
+pub struct Histogram<const B: usize> {
+    counts: MainCtxCell<[u16; B]>,
+    width: u32,
+}
+
+impl<const B: usize> Histogram<B> {
+    /// Create a new empty histogram with the bucket `width`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `width` is zero.
+    #[inline(always)]
+    pub const fn new(width: u32) -> Self {
+        const { assert!(B > 0) };
+        assert!(width > 0, "Histogram: width must not be zero");
+        Self {
+            counts: MainCtxCell::new([0; B]),
+            width,
+        }
+    }
+
+    /// Get the bucket width.
+    #[inline(always)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Count the `value` in its bucket.
+    #[inline(always)]
+    pub fn record(&self, m: &MainCtx<'_>, value: u32) {
+        let bucket = ((value / self.width) as usize).min(B - 1);
+        let count = self.counts.index(m, bucket);
+        self.counts.set_index(m, bucket, count.saturating_add(1));
+    }
+
+    /// Get the counts of all buckets.
+    #[inline(always)]
+    pub fn buckets(&self, m: &MainCtx<'_>) -> [u16; B] {
+        self.counts.get(m)
+    }
+
+    /// Get the total number of recorded values.
+    ///
+    /// Saturated bucket counts are included with `u16::MAX`.
+    #[inline(always)]
+    pub fn total(&self, m: &MainCtx<'_>) -> u32 {
+        self.buckets(m).iter().map(|&c| u32::from(c)).sum()
+    }
+
+    /// Clear all buckets.
+    #[inline(always)]
+    pub fn reset(&self, m: &MainCtx<'_>) {
+        self.counts.set(m, [0; B]);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        let m = unsafe { MainCtx::new() };
+
+        let hist: Histogram<4> = Histogram::new(10);
+        assert_eq!(hist.buckets(&m), [0; 4]);
+        assert_eq!(hist.width(), 10);
+
+        for value in [0, 9, 10, 15, 19, 20, 29, 30, 1000, u32::MAX] {
+            hist.record(&m, value);
+        }
+        assert_eq!(hist.buckets(&m), [2, 3, 2, 3]);
+        assert_eq!(hist.total(&m), 10);
+
+        hist.reset(&m);
+        assert_eq!(hist.total(&m), 0);
+    }
+
+    #[test]
+    fn test_histogram_saturation() {
+        let m = unsafe { MainCtx::new() };
+
+        let hist: Histogram<1> = Histogram::new(1);
+        for _ in 0..u32::from(u16::MAX) + 5 {
+            hist.record(&m, 3);
+        }
+        assert_eq!(hist.buckets(&m), [u16::MAX]);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
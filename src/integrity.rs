@@ -0,0 +1,325 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! RAM integrity checks.
+
+use crate::{
+    cell::{IrqCtxCell, MainCtxCell},
+    context::{IrqCtx, MainCtx},
+};
+use core::hash::Hasher;
+
+/// Calculate the Fletcher-16 checksum of `data`.
+#[inline]
+pub const fn fletcher16(data: &[u8]) -> u16 {
+    let mut a: u16 = 0;
+    let mut b: u16 = 0;
+    let mut i = 0;
+    while i < data.len() {
+        a = (a + data[i] as u16) % 255;
+        b = (b + a) % 255;
+        i += 1;
+    }
+    (b << 8) | a
+}
+
+/// Initial value of the CRC-16/CCITT-FALSE checksum.
+pub const CRC16_CCITT_INIT: u16 = 0xFFFF;
+
+/// Update the CRC-16/CCITT-FALSE checksum `crc` with the `byte`.
+///
+/// The polynomial is 0x1021. Start with [CRC16_CCITT_INIT].
+/// This is calculated bitwise without a lookup table to save flash memory.
+#[inline]
+pub const fn crc16_ccitt_update(crc: u16, byte: u8) -> u16 {
+    let mut crc = crc ^ ((byte as u16) << 8);
+    let mut i = 0;
+    while i < 8 {
+        crc = if crc & 0x8000 != 0 {
+            (crc << 1) ^ 0x1021
+        } else {
+            crc << 1
+        };
+        i += 1;
+    }
+    crc
+}
+
+/// Calculate the CRC-16/CCITT-FALSE checksum of `data`.
+#[inline]
+pub const fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc = CRC16_CCITT_INIT;
+    let mut i = 0;
+    while i < data.len() {
+        crc = crc16_ccitt_update(crc, data[i]);
+        i += 1;
+    }
+    crc
+}
+
+/// 32 bit FNV-1a hasher.
+///
+/// This is a small and fast non-cryptographic hash for `no_std`.
+/// The hash of a `T: Hash` value depends on the byte order of the target.
+#[derive(Clone, Copy, Debug)]
+pub struct Fnv1a(u32);
+
+impl Fnv1a {
+    /// Create a new hasher.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self(0x811C_9DC5)
+    }
+
+    /// Get the 32 bit hash value.
+    #[inline(always)]
+    pub const fn finish32(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Hasher for Fnv1a {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ u32::from(b)).wrapping_mul(0x0100_0193);
+        }
+    }
+
+    #[inline(always)]
+    fn finish(&self) -> u64 {
+        u64::from(self.0)
+    }
+}
+
+impl Default for Fnv1a {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Byte array with a stored checksum that can only be accessed from `main()` context.
+///
+/// The checksum is updated on every write.
+/// Reads verify the data against the stored checksum.
+/// This detects corruption of the data in RAM, e.g. by glitches.
+pub struct Checked<const N: usize> {
+    data: MainCtxCell<[u8; N]>,
+    sum: MainCtxCell<u16>,
+}
+
+impl<const N: usize> Checked<N> {
+    /// Create a new checked array with the `initial` data.
+    #[inline(always)]
+    pub const fn new(initial: [u8; N]) -> Self {
+        Self {
+            data: MainCtxCell::new(initial),
+            sum: MainCtxCell::new(fletcher16(&initial)),
+        }
+    }
+
+    /// Write the `data` and update the stored checksum.
+    #[inline(always)]
+    pub fn set(&self, m: &MainCtx<'_>, data: [u8; N]) {
+        self.data.set(m, data);
+        self.sum.set(m, fletcher16(&data));
+    }
+
+    /// Returns `true`, if the data matches the stored checksum.
+    #[inline(always)]
+    pub fn verify(&self, m: &MainCtx<'_>) -> bool {
+        self.data.checksum(m) == self.sum.get(m)
+    }
+
+    /// Get a copy of the data, if it matches the stored checksum.
+    ///
+    /// Returns `None`, if the data is corrupted.
+    #[inline(always)]
+    pub fn get(&self, m: &MainCtx<'_>) -> Option<[u8; N]> {
+        let data = self.data.get(m);
+        (fletcher16(&data) == self.sum.get(m)).then_some(data)
+    }
+
+    /// Get the raw data cell without checksum verification.
+    ///
+    /// Modifications of the data through this cell need a subsequent [Self::set]
+    /// to update the checksum.
+    #[inline(always)]
+    pub fn raw(&self) -> &MainCtxCell<[u8; N]> {
+        &self.data
+    }
+}
+
+impl<const N: usize> Default for Checked<N> {
+    fn default() -> Self {
+        Self::new([0; N])
+    }
+}
+
+/// Running CRC-16/CCITT-FALSE checksum that can only be accessed from interrupt context.
+///
+/// This is useful for receive interrupts that check the CRC of a byte stream
+/// without involving the main loop.
+pub struct CrcAccum {
+    crc: IrqCtxCell<u16>,
+}
+
+impl CrcAccum {
+    /// Create a new accumulator without data.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            crc: IrqCtxCell::new(CRC16_CCITT_INIT),
+        }
+    }
+
+    /// Add the `byte` to the checksum from interrupt context.
+    #[inline(always)]
+    pub fn update(&self, irq: &IrqCtx<'_>, byte: u8) {
+        self.crc
+            .set(irq, crc16_ccitt_update(self.crc.get(irq), byte));
+    }
+
+    /// Add all bytes of `data` to the checksum from interrupt context.
+    #[inline(always)]
+    pub fn update_slice(&self, irq: &IrqCtx<'_>, data: &[u8]) {
+        for &byte in data {
+            self.update(irq, byte);
+        }
+    }
+
+    /// Get the checksum of all bytes since the last finalization from interrupt context.
+    ///
+    /// The accumulator is reset for the next stream of data.
+    #[inline(always)]
+    pub fn finalize(&self, irq: &IrqCtx<'_>) -> u16 {
+        self.crc.replace(irq, CRC16_CCITT_INIT)
+    }
+
+    /// Discard the accumulated checksum from interrupt context.
+    #[inline(always)]
+    pub fn reset(&self, irq: &IrqCtx<'_>) {
+        self.crc.set(irq, CRC16_CCITT_INIT);
+    }
+}
+
+impl Default for CrcAccum {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use core::hash::Hash;
+
+    #[test]
+    fn test_fnv1a() {
+        let hash = |data: &[u8]| {
+            let mut h = Fnv1a::new();
+            h.write(data);
+            h.finish32()
+        };
+        assert_eq!(hash(b""), 0x811C_9DC5);
+        assert_eq!(hash(b"a"), 0xE40C_292C);
+        assert_eq!(hash(b"foobar"), 0xBF9C_F968);
+    }
+
+    #[test]
+    fn test_cell_hash() {
+        let m = unsafe { MainCtx::new() };
+
+        #[derive(Hash)]
+        struct State {
+            speed: u16,
+            name: [u8; 4],
+        }
+
+        let a = MainCtxCell::new(State {
+            speed: 100,
+            name: *b"left",
+        });
+        let b = MainCtxCell::new(State {
+            speed: 100,
+            name: *b"left",
+        });
+        assert_eq!(a.hash(&m), b.hash(&m));
+        let mut h = Fnv1a::new();
+        a.as_ref(&m).hash(&mut h);
+        assert_eq!(a.hash(&m), h.finish32());
+
+        b.replace(
+            &m,
+            State {
+                speed: 101,
+                name: *b"left",
+            },
+        );
+        assert_ne!(a.hash(&m), b.hash(&m));
+    }
+
+    #[test]
+    fn test_fletcher16() {
+        assert_eq!(fletcher16(b""), 0x0000);
+        assert_eq!(fletcher16(b"abcde"), 0xC8F0);
+        assert_eq!(fletcher16(b"abcdef"), 0x2057);
+        assert_eq!(fletcher16(b"abcdefgh"), 0x0627);
+    }
+
+    #[test]
+    fn test_crc16_ccitt() {
+        assert_eq!(crc16_ccitt(b""), 0xFFFF);
+        assert_eq!(crc16_ccitt(b"123456789"), 0x29B1);
+        assert_eq!(crc16_ccitt(b"A"), 0xB915);
+    }
+
+    #[test]
+    fn test_crc_accum() {
+        let irq = unsafe { IrqCtx::new() };
+
+        let crc = CrcAccum::new();
+        assert_eq!(crc.finalize(&irq), 0xFFFF);
+
+        for &byte in b"123456789" {
+            crc.update(&irq, byte);
+        }
+        assert_eq!(crc.finalize(&irq), 0x29B1);
+
+        crc.update_slice(&irq, b"A");
+        assert_eq!(crc.finalize(&irq), 0xB915);
+
+        crc.update_slice(&irq, b"garbage");
+        crc.reset(&irq);
+        crc.update_slice(&irq, b"1234");
+        crc.update_slice(&irq, b"56789");
+        assert_eq!(crc.finalize(&irq), 0x29B1);
+    }
+
+    #[test]
+    fn test_checked() {
+        let m = unsafe { MainCtx::new() };
+
+        let cfg: Checked<5> = Checked::new(*b"abcde");
+        assert!(cfg.verify(&m));
+        assert_eq!(cfg.get(&m), Some(*b"abcde"));
+
+        cfg.set(&m, *b"edcba");
+        assert!(cfg.verify(&m));
+        assert_eq!(cfg.get(&m), Some(*b"edcba"));
+
+        // Corrupt the data.
+        cfg.raw().set(&m, *b"edcbb");
+        assert!(!cfg.verify(&m));
+        assert_eq!(cfg.get(&m), None);
+
+        let zero: Checked<2> = Checked::default();
+        assert_eq!(zero.get(&m), Some([0; 2]));
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,182 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Allocation free intrusive queue in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+use core::{
+    ops::{Deref, DerefMut},
+    ptr::null_mut,
+};
+
+/// A node of an [IntrusiveQueue].
+///
+/// The node lives in user storage, e.g. in a `static`,
+/// and carries the link to the next node in the queue.
+/// The value of the node is accessible via `Deref` and `DerefMut`.
+pub struct Node<T> {
+    value: T,
+    next: *mut Node<T>,
+}
+
+impl<T> Node<T> {
+    /// Create a new unlinked node holding `value`.
+    #[inline(always)]
+    pub const fn new(value: T) -> Self {
+        Self {
+            value,
+            next: null_mut(),
+        }
+    }
+}
+
+impl<T> Deref for Node<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Node<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+// SAFETY: If T is Send, then we can Send the whole object.
+// The link is only accessed by the queue while the queue owns the node.
+unsafe impl<T: Send> Send for Node<T> {}
+
+/// First in, first out queue of [Node]s that can only be accessed from `main()` context.
+///
+/// The queue does not allocate. The nodes are provided by the user as `&'static mut`
+/// and the ownership of the node is moved into the queue by [Self::enqueue].
+/// [Self::dequeue] moves the ownership back to the caller.
+///
+/// This is useful for zero-alloc queuing of tasks or messages in `main()` context.
+pub struct IntrusiveQueue<T: 'static> {
+    head: MainCtxCell<*mut Node<T>>,
+    tail: MainCtxCell<*mut Node<T>>,
+}
+
+impl<T: 'static> IntrusiveQueue<T> {
+    /// Create a new empty queue.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            head: MainCtxCell::new(null_mut()),
+            tail: MainCtxCell::new(null_mut()),
+        }
+    }
+
+    /// Append `node` to the end of the queue.
+    #[inline(always)]
+    pub fn enqueue(&self, m: &MainCtx<'_>, node: &'static mut Node<T>) {
+        node.next = null_mut();
+        let node: *mut Node<T> = node;
+        let tail = self.tail.get(m);
+        if tail.is_null() {
+            self.head.set(m, node);
+        } else {
+            // SAFETY:
+            // The tail has been moved into the queue by `enqueue` and it is still owned by the queue.
+            // Therefore, there is no other reference to it.
+            unsafe { (*tail).next = node };
+        }
+        self.tail.set(m, node);
+    }
+
+    /// Remove the node at the front of the queue.
+    ///
+    /// Returns `None`, if the queue is empty.
+    #[inline(always)]
+    pub fn dequeue(&self, m: &MainCtx<'_>) -> Option<&'static mut Node<T>> {
+        let head = self.head.get(m);
+        if head.is_null() {
+            return None;
+        }
+        // SAFETY:
+        // The head has been moved into the queue by `enqueue` as `&'static mut`.
+        // The queue gives up its ownership here, so the returned reference is unique.
+        let node = unsafe { &mut *head };
+        self.head.set(m, node.next);
+        if node.next.is_null() {
+            self.tail.set(m, null_mut());
+        }
+        node.next = null_mut();
+        Some(node)
+    }
+
+    /// Returns `true`, if the queue is empty.
+    #[inline(always)]
+    pub fn is_empty(&self, m: &MainCtx<'_>) -> bool {
+        self.head.get(m).is_null()
+    }
+}
+
+impl<T: 'static> Default for IntrusiveQueue<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: If T is Send, then we can Send the whole object.
+// The queue owns the linked nodes.
+unsafe impl<T: Send> Send for IntrusiveQueue<T> {}
+
+// SAFETY:
+// The links can only be accessed with a `MainCtx`.
+// Therefore, the nodes are only accessed from `main()` context.
+unsafe impl<T: Send> Sync for IntrusiveQueue<T> {}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use std::boxed::Box;
+
+    fn node(value: u8) -> &'static mut Node<u8> {
+        Box::leak(Box::new(Node::new(value)))
+    }
+
+    #[test]
+    fn test_intrusive_queue() {
+        static QUEUE: IntrusiveQueue<u8> = IntrusiveQueue::new();
+
+        let m = unsafe { MainCtx::new() };
+
+        assert!(QUEUE.is_empty(&m));
+        assert!(QUEUE.dequeue(&m).is_none());
+
+        QUEUE.enqueue(&m, node(1));
+        QUEUE.enqueue(&m, node(2));
+        QUEUE.enqueue(&m, node(3));
+        assert!(!QUEUE.is_empty(&m));
+
+        let first = QUEUE.dequeue(&m).unwrap();
+        assert_eq!(**first, 1);
+        **first = 4;
+        // Requeue the node at the end.
+        QUEUE.enqueue(&m, first);
+
+        assert_eq!(**QUEUE.dequeue(&m).unwrap(), 2);
+        assert_eq!(**QUEUE.dequeue(&m).unwrap(), 3);
+        let last = QUEUE.dequeue(&m).unwrap();
+        assert_eq!(**last, 4);
+        assert!(QUEUE.dequeue(&m).is_none());
+        assert!(QUEUE.is_empty(&m));
+
+        QUEUE.enqueue(&m, last);
+        QUEUE.enqueue(&m, node(5));
+        assert_eq!(**QUEUE.dequeue(&m).unwrap(), 4);
+        assert_eq!(**QUEUE.dequeue(&m).unwrap(), 5);
+        assert!(QUEUE.is_empty(&m));
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -92,12 +92,79 @@
 //! }
 //! ```
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+// Make the `::avr_context` paths of the derive macros work in our own tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as avr_context;
+
+pub mod asyncpoll;
+pub mod average;
+pub mod barrier;
+#[cfg(feature = "alloc")]
+pub mod boxed;
+pub mod buffer;
+pub mod bus;
+pub mod cached;
+pub mod capture;
 pub mod cell;
+#[cfg(feature = "codegen-test")]
+pub mod codegen;
 pub mod context;
+pub mod coop;
+pub mod debounce;
+pub mod decimate;
+pub mod deferred;
+pub mod dirty;
+pub mod dispatch;
+pub mod doublebuf;
+pub mod dropqueue;
+pub mod edge;
+pub mod errors;
+pub mod flag;
+pub mod frame;
+#[cfg(feature = "heapless")]
+pub mod heapless_spsc;
+pub mod histogram;
+pub mod integrity;
+pub mod intrusive;
+pub mod lru;
+pub mod migr;
+#[cfg(feature = "panic-dump")]
+pub mod panicdump;
+pub mod phase;
+pub mod pin;
+pub mod poison;
+pub mod pwm;
+pub mod regshadow;
+mod ring;
+pub mod ringlog;
+pub mod rng;
+#[cfg(feature = "rtt")]
+pub mod rtt;
+pub mod saturating;
+pub mod scheduler;
+pub mod sg;
+#[cfg(all(feature = "std-test", not(target_arch = "avr")))]
+pub mod sim;
+pub mod spsc;
+#[cfg(any(test, all(debug_assertions, target_arch = "avr")))]
+mod stackguard;
+pub mod state;
+pub mod stepper;
+pub mod time;
+pub mod timer;
+#[cfg(all(feature = "access-trace", not(target_arch = "avr")))]
+pub mod trace;
+pub mod watch;
 
 pub use crate::{
-    cell::{InitCtxCell, MainCtxCell},
-    context::{InitCtx, IrqCtx, MainCtx},
+    cell::{
+        InitCtxCell, InitOrDefault, IrqCtxCell, MainCtxCell, MainCtxCellArray, MainCtxDynCell,
+        TaggedIrqCtxCell, ZeroValue,
+    },
+    context::{CtxId, FfiCtx, InitCtx, InitPhaseToken, IrqCtx, MainCtx, TaggedIrqCtx},
 };
 
 /// Re-export of `critical_section::CriticalSection`.
@@ -107,6 +174,33 @@ pub type Mutex<T> = critical_section::Mutex<T>;
 /// Re-export of `critical_section::with`.
 pub use critical_section::with as with_cs;
 
+/// Derive getters and setters for structs of `MainCtxCell`s.
+///
+/// This is only available with the `derive` feature.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use avr_context::{MainCtx, MainCtxAccess, MainCtxCell};
+///
+/// #[derive(MainCtxAccess)]
+/// struct DeviceState {
+///     pub speed: MainCtxCell<u16>,
+///     pub enabled: MainCtxCell<bool>,
+/// }
+///
+/// fn stop(state: &DeviceState, m: &MainCtx<'_>) {
+///     if state.enabled(m) {
+///         state.set_speed(m, 0);
+///     }
+/// }
+/// # }
+/// ```
+#[cfg(feature = "derive")]
+pub use avr_context_derive::MainCtxAccess;
+
 #[cfg(test)]
 #[allow(clippy::undocumented_unsafe_blocks)]
 mod test {
@@ -133,6 +227,231 @@ mod test {
         assert_eq!(c, 44);
     }
 
+    #[test]
+    fn test_main_ctx_map_ref() {
+        let ctx = unsafe { MainCtx::new() };
+
+        struct Config {
+            id: u8,
+            name: [u8; 3],
+        }
+
+        let a: MainCtxCell<Config> = MainCtxCell::new(Config {
+            id: 7,
+            name: *b"abc",
+        });
+        let id: &u8 = a.map_ref(&ctx, |c| &c.id);
+        let name: &[u8] = a.map_ref(&ctx, |c| &c.name[1..]);
+        assert_eq!(*id, 7);
+        assert_eq!(name, b"bc");
+    }
+
+    #[test]
+    fn test_main_ctx_ensure() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<Option<[u8; 2]>> = MainCtxCell::new(None);
+        let mut calls = 0;
+        let v = a.ensure(&ctx, || {
+            calls += 1;
+            [1, 2]
+        });
+        assert_eq!(v, [1, 2]);
+        let v = a.ensure(&ctx, || {
+            calls += 1;
+            [3, 4]
+        });
+        assert_eq!(v, [1, 2]);
+        assert_eq!(calls, 1);
+        assert_eq!(a.get(&ctx), Some([1, 2]));
+
+        let b: MainCtxCell<Option<u8>> = MainCtxCell::new(Some(5));
+        assert_eq!(b.ensure(&ctx, || unreachable!()), 5);
+
+        // The cell can still be overwritten after the initialization.
+        b.set(&ctx, None);
+        assert_eq!(b.ensure(&ctx, || 6), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "reentrant")]
+    fn test_main_ctx_ensure_reentrant() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<Option<u8>> = MainCtxCell::new(None);
+        a.ensure(&ctx, || a.ensure(&ctx, || 1) + 1);
+    }
+
+    #[test]
+    fn test_main_ctx_set_and_changed() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<u8> = MainCtxCell::new(1);
+        assert!(!a.set_and_changed(&ctx, 1));
+        assert!(a.set_and_changed(&ctx, 2));
+        assert_eq!(a.get(&ctx), 2);
+        assert!(!a.set_and_changed(&ctx, 2));
+
+        let b: MainCtxCell<f32> = MainCtxCell::new(f32::NAN);
+        assert!(b.set_and_changed(&ctx, f32::NAN));
+    }
+
+    #[test]
+    fn test_main_ctx_into_inner() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let mut a: MainCtxCell<[u16; 2]> = MainCtxCell::new([1, 2]);
+        a.get_mut()[1] = 3;
+        assert_eq!(a.get(&ctx), [1, 3]);
+        *a.get_mut() = [4, 5];
+        assert_eq!(a.into_inner(), [4, 5]);
+
+        let b: MainCtxCell<std::vec::Vec<u8>> = MainCtxCell::new(vec![6]);
+        assert_eq!(b.into_inner(), [6]);
+    }
+
+    #[test]
+    fn test_main_ctx_mutex() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<u16> = MainCtxCell::from_mutex(Mutex::new(core::cell::Cell::new(1)));
+        assert_eq!(a.get(&ctx), 1);
+
+        with_cs(|cs| unsafe { a.as_bare_mutex() }.borrow(cs).set(2));
+        assert_eq!(a.get(&ctx), 2);
+
+        let mutex = a.into_mutex();
+        assert_eq!(with_cs(|cs| mutex.borrow(cs).get()), 2);
+        let b = MainCtxCell::from_mutex(mutex);
+        assert_eq!(b.into_inner(), 2);
+    }
+
+    #[test]
+    fn test_main_ctx_snapshot() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<std::string::String> = MainCtxCell::new("hello".into());
+        let snap = a.snapshot(&ctx);
+        assert_eq!(snap, "hello");
+        a.replace(&ctx, "world".into());
+        assert_eq!(snap, "hello");
+        assert_eq!(a.snapshot(&ctx), "world");
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn test_main_ctx_snapshot_heapless() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<heapless::String<8>> = MainCtxCell::new(heapless::String::new());
+        a.replace(&ctx, heapless::String::try_from("state").unwrap());
+        let snap = a.snapshot(&ctx);
+        assert_eq!(snap.as_str(), "state");
+        assert_eq!(a.as_ref(&ctx).as_str(), "state");
+    }
+
+    #[cfg(feature = "fixed")]
+    #[test]
+    fn test_main_ctx_fixed() {
+        use fixed::types::I16F16;
+
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<I16F16> = MainCtxCell::new(I16F16::from_num(1.5));
+        assert_eq!(a.add(&ctx, I16F16::from_num(0.25)), I16F16::from_num(1.75));
+        assert_eq!(a.mul(&ctx, I16F16::from_num(2)), I16F16::from_num(3.5));
+        assert_eq!(a.get(&ctx), I16F16::from_num(3.5));
+
+        assert_eq!(
+            a.saturating_mul_add(&ctx, I16F16::from_num(0.5), I16F16::from_num(-1)),
+            I16F16::from_num(0.75)
+        );
+        assert_eq!(a.saturating_add(&ctx, I16F16::MAX), I16F16::MAX);
+        assert_eq!(a.saturating_mul(&ctx, I16F16::from_num(-2)), I16F16::MIN);
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn test_main_ctx_format_into() {
+        use core::fmt::Write as _;
+
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<heapless::String<16>> = MainCtxCell::new(heapless::String::new());
+        a.format_into(&ctx, |s| write!(s, "rpm={}", 1234)).unwrap();
+        assert_eq!(a.as_ref(&ctx).as_str(), "rpm=1234");
+
+        let len = a.format_into(&ctx, |s| {
+            write!(s, "{:02}", 7).unwrap();
+            s.len()
+        });
+        assert_eq!(len, 2);
+        assert_eq!(a.as_ref(&ctx).as_str(), "07");
+
+        assert!(a.format_into(&ctx, |s| write!(s, "{}", u64::MAX)).is_err());
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn test_main_ctx_try_modify() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<heapless::Vec<u8, 2>> = MainCtxCell::new(heapless::Vec::new());
+        assert_eq!(a.try_modify(&ctx, |v| v.push(1)), Ok(()));
+        assert_eq!(a.try_modify(&ctx, |v| v.push(2).map(|_| v.len())), Ok(2));
+        assert_eq!(a.try_modify(&ctx, |v| v.push(3)), Err(3));
+        assert_eq!(a.as_ref(&ctx).as_slice(), &[1, 2]);
+
+        assert_eq!(
+            a.try_modify(&ctx, |v| {
+                v.pop();
+                v.push(4)
+            }),
+            Ok(())
+        );
+        assert_eq!(a.as_ref(&ctx).as_slice(), &[1, 4]);
+    }
+
+    #[test]
+    fn test_main_ctx_reset_fill() {
+        static BUF: MainCtxCell<[u16; 4]> = MainCtxCell::zeroed();
+        static STATE: MainCtxCell<(Option<u8>, bool)> = MainCtxCell::new((None, false));
+
+        let ctx = unsafe { MainCtx::new() };
+
+        assert_eq!(BUF.get(&ctx), [0; 4]);
+        BUF.fill(&ctx, 0xAA55);
+        assert_eq!(BUF.get(&ctx), [0xAA55; 4]);
+        BUF.zero(&ctx);
+        assert_eq!(BUF.get(&ctx), [0; 4]);
+        BUF.set(&ctx, [1, 2, 3, 4]);
+        BUF.reset(&ctx);
+        assert_eq!(BUF.get(&ctx), [0; 4]);
+
+        STATE.set(&ctx, (Some(5), true));
+        STATE.reset(&ctx);
+        assert_eq!(STATE.get(&ctx), (None, false));
+
+        let a: MainCtxCell<std::vec::Vec<u8>> = MainCtxCell::new(std::vec![1, 2, 3]);
+        a.reset(&ctx);
+        assert!(a.as_ref(&ctx).is_empty());
+
+        let f: MainCtxCell<[Option<f32>; 2]> = MainCtxCell::zeroed();
+        assert_eq!(f.get(&ctx), [None, None]);
+    }
+
+    #[test]
+    fn test_main_ctx_bytes() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<u32> = MainCtxCell::new(0x1234_5678);
+        let bytes = unsafe { a.with_bytes(&ctx, |b| <[u8; 4]>::try_from(b).unwrap()) };
+        assert_eq!(bytes, 0x1234_5678_u32.to_ne_bytes());
+
+        let sum = unsafe { a.with_bytes(&ctx, |b| b.iter().map(|b| *b as u32).sum::<u32>()) };
+        assert_eq!(sum, 0x12 + 0x34 + 0x56 + 0x78);
+    }
+
     #[test]
     fn test_main_ctx_array() {
         let ctx = unsafe { MainCtx::new() };
@@ -143,6 +462,259 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_main_ctx_cas_ptr() {
+        static CONFIG_A: [u8; 2] = [1, 2];
+        static CONFIG_B: [u8; 2] = [1, 2];
+
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<&'static [u8; 2]> = MainCtxCell::new(&CONFIG_A);
+        assert!(!a.cas_ptr(&ctx, &CONFIG_B, &CONFIG_B));
+        assert!(core::ptr::eq(a.get(&ctx), &CONFIG_A));
+        assert!(a.cas_ptr(&ctx, &CONFIG_A, &CONFIG_B));
+        assert!(core::ptr::eq(a.get(&ctx), &CONFIG_B));
+
+        let b: MainCtxCell<&'static [u8]> = MainCtxCell::new(&CONFIG_A);
+        // Same address, but different length. The length is not compared.
+        assert!(b.cas_ptr(&ctx, &CONFIG_A[..1], &CONFIG_B));
+        assert!(core::ptr::eq(b.get(&ctx), &CONFIG_B[..]));
+        assert!(!b.cas_ptr(&ctx, &CONFIG_A, &CONFIG_A));
+
+        #[derive(Debug)]
+        #[repr(C)]
+        struct Wrapper(u8);
+        static WRAPPER: Wrapper = Wrapper(3);
+
+        // Two different trait objects of the same address. The vtable is not compared.
+        let c: MainCtxCell<&'static dyn core::fmt::Debug> = MainCtxCell::new(&WRAPPER);
+        let inner: &'static dyn core::fmt::Debug = &WRAPPER.0;
+        assert!(!core::ptr::eq(c.get(&ctx), inner));
+        assert!(c.cas_ptr(&ctx, inner, &CONFIG_B));
+        assert!(core::ptr::addr_eq(c.get(&ctx), &CONFIG_B));
+        assert!(!c.cas_ptr(&ctx, &WRAPPER, &CONFIG_A));
+    }
+
+    #[test]
+    fn test_main_ctx_wrapping() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let ticks: MainCtxCell<core::num::Wrapping<u16>> =
+            MainCtxCell::new(core::num::Wrapping(0xFFFE));
+        ticks.wrapping_add(&ctx, 1);
+        assert_eq!(ticks.get_raw(&ctx), 0xFFFF);
+        ticks.wrapping_add(&ctx, 1);
+        assert_eq!(ticks.get_raw(&ctx), 0);
+        ticks.wrapping_add(&ctx, 0xFFFF);
+        assert_eq!(ticks.get_raw(&ctx), 0xFFFF);
+        assert_eq!(ticks.get(&ctx), core::num::Wrapping(0xFFFF));
+
+        let delta: MainCtxCell<core::num::Wrapping<i8>> =
+            MainCtxCell::new(core::num::Wrapping(127));
+        delta.wrapping_add(&ctx, 1);
+        assert_eq!(delta.get_raw(&ctx), -128);
+    }
+
+    #[test]
+    fn test_main_ctx_array_checksum() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<[u8; 6]> = MainCtxCell::new(*b"abcdef");
+        assert_eq!(a.checksum(&ctx), 0x2057);
+        a.set(&ctx, [0; 6]);
+        assert_eq!(a.checksum(&ctx), 0);
+    }
+
+    #[test]
+    fn test_main_ctx_array_index() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<[u16; 3]> = MainCtxCell::new([1, 2, 3]);
+        assert_eq!(a.index(&ctx, 1), 2);
+        a.set_index(&ctx, 2, 30);
+        assert_eq!(a.get(&ctx), [1, 2, 30]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_main_ctx_array_index_out_of_bounds() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<[u16; 3]> = MainCtxCell::new([1, 2, 3]);
+        a.index(&ctx, 3);
+    }
+
+    #[test]
+    fn test_main_ctx_cell_array() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCellArray<u8, 4> = MainCtxCellArray::new_filled(5);
+        assert_eq!(a.len(), 4);
+        assert!(!a.is_empty());
+        assert_eq!(a.get_all(&ctx), [5; 4]);
+
+        a.set(&ctx, 0, 1);
+        assert_eq!(
+            a.update(&ctx, 3, |e| {
+                *e += 1;
+                *e
+            }),
+            6
+        );
+        assert_eq!(a.get(&ctx, 0), 1);
+        assert_eq!(a.get(&ctx, 3), 6);
+        assert_eq!(a.as_cell().get(&ctx), [1, 5, 5, 6]);
+
+        let b: MainCtxCellArray<i32, 0> = MainCtxCellArray::default();
+        assert!(b.is_empty());
+    }
+
+    #[test]
+    fn test_main_ctx_drain_prefix() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<([u8; 6], usize)> = MainCtxCell::new(([1, 2, 3, 4, 5, 0], 5));
+
+        let mut out = [0; 2];
+        assert_eq!(a.drain_prefix(&ctx, &mut out), 2);
+        assert_eq!(out, [1, 2]);
+        assert_eq!(a.get(&ctx).1, 3);
+        assert_eq!(a.get(&ctx).0[..3], [3, 4, 5]);
+
+        let mut out = [0; 8];
+        assert_eq!(a.drain_prefix(&ctx, &mut out), 3);
+        assert_eq!(out[..3], [3, 4, 5]);
+        assert_eq!(a.get(&ctx).1, 0);
+
+        assert_eq!(a.drain_prefix(&ctx, &mut out), 0);
+        assert_eq!(a.drain_prefix(&ctx, &mut []), 0);
+
+        a.set(&ctx, ([7; 6], 6));
+        let mut out = [0; 6];
+        assert_eq!(a.drain_prefix(&ctx, &mut out), 6);
+        assert_eq!(out, [7; 6]);
+        assert_eq!(a.get(&ctx).1, 0);
+    }
+
+    #[test]
+    fn test_main_ctx_array_refs() {
+        let ctx = unsafe { MainCtx::new() };
+
+        fn is_less(a: &u16, b: &u16) -> bool {
+            a < b
+        }
+
+        let a: MainCtxCell<[u16; 3]> = MainCtxCell::new([1, 2, 3]);
+        let [r0, r1, r2] = a.refs(&ctx);
+        assert!(is_less(r0, r1));
+        assert!(is_less(r1, r2));
+
+        a.with_each_mut(&ctx, |e| *e *= 10);
+        assert_eq!(a.get(&ctx), [10, 20, 30]);
+    }
+
+    main_cells! {
+        /// Test state.
+        struct TestState {
+            speed: u16 = 100,
+            mode: [u8; 2] = [1, 2],
+            /// Enable flag.
+            enabled: bool = false,
+        }
+    }
+
+    static TEST_STATE: TestState = TestState::new();
+
+    #[test]
+    fn test_main_cells() {
+        let ctx = unsafe { MainCtx::new() };
+
+        assert_eq!(TEST_STATE.speed.get(&ctx), 100);
+        assert_eq!(TEST_STATE.mode.get(&ctx), [1, 2]);
+        assert!(!TEST_STATE.enabled.get(&ctx));
+        TEST_STATE.enabled.set(&ctx, true);
+        assert!(TEST_STATE.enabled.get(&ctx));
+
+        let state = TestState::default();
+        assert_eq!(state.speed.get(&ctx), 100);
+    }
+
+    #[test]
+    fn test_main_ctx_get2_set2() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let x: MainCtxCell<i16> = MainCtxCell::new(1);
+        let y: MainCtxCell<u8> = MainCtxCell::new(2);
+        assert_eq!(cell::get2(&x, &y, &ctx), (1, 2));
+        cell::set2(&x, &y, &ctx, (-3, 4));
+        assert_eq!(x.get(&ctx), -3);
+        assert_eq!(y.get(&ctx), 4);
+        assert_eq!(cell::get2(&x, &y, &ctx), (-3, 4));
+    }
+
+    #[test]
+    fn test_main_ctx_run_with_stack_guard() {
+        let ctx = unsafe { MainCtx::new() };
+
+        assert_eq!(ctx.run_with_stack_guard(128, || 3), 3);
+    }
+
+    #[test]
+    fn test_main_ctx_for_each() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let channels: [MainCtxCell<u16>; 4] = MainCtxCell::new_array(10);
+        channels[2].set(&ctx, 20);
+        let mut visited = 0;
+        cell::for_each(&channels, &ctx, |ch, m| {
+            ch.set(m, ch.get(m) + 1);
+            visited += 1;
+        });
+        assert_eq!(visited, 4);
+        assert_eq!(channels.each_ref().map(|ch| ch.get(&ctx)), [11, 11, 21, 11]);
+        cell::for_each(&channels[..0], &ctx, |_, _| unreachable!());
+    }
+
+    #[test]
+    fn test_main_ctx_dyn_cell() {
+        trait State {
+            fn run(&mut self) -> u16;
+        }
+
+        struct Counter(u16);
+
+        impl State for Counter {
+            fn run(&mut self) -> u16 {
+                self.0 += 1;
+                self.0
+            }
+        }
+
+        static STATE: MainCtxDynCell<dyn State + Send> = MainCtxDynCell::new();
+
+        let ctx = unsafe { MainCtx::new() };
+
+        let counter10 = Box::into_raw(Box::new(Counter(10)));
+        let counter20 = Box::into_raw(Box::new(Counter(20)));
+
+        assert_eq!(STATE.with(&ctx, |s| s.run()), None);
+        assert!(STATE.set(&ctx, unsafe { &mut *counter10 }).is_none());
+        assert_eq!(STATE.with(&ctx, |s| s.run()), Some(11));
+        assert_eq!(STATE.with(&ctx, |s| s.run()), Some(12));
+
+        let nested = STATE.with(&ctx, |_| STATE.with(&ctx, |s| s.run()));
+        assert_eq!(nested, Some(None));
+
+        let old = STATE.with(&ctx, |_| STATE.set(&ctx, unsafe { &mut *counter20 }));
+        assert!(matches!(old, Some(None)));
+        assert_eq!(STATE.with(&ctx, |s| s.run()), Some(21));
+        assert!(STATE.take(&ctx).is_some());
+        assert!(STATE.take(&ctx).is_none());
+
+        drop(unsafe { Box::from_raw(counter10) });
+        drop(unsafe { Box::from_raw(counter20) });
+    }
+
     #[repr(transparent)]
     struct Dropme<T>(pub T);
 
@@ -182,11 +754,579 @@ mod test {
         let _: MainCtx<'_> = ctx;
     }
 
+    #[test]
+    fn test_init_ctx_reinit() {
+        struct DropCount<'a>(u8, &'a core::cell::Cell<u8>);
+
+        impl Drop for DropCount<'_> {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+            }
+        }
+
+        let drops = core::cell::Cell::new(0);
+        let cell: InitCtxCell<DropCount<'_>> = unsafe { InitCtxCell::uninit() };
+
+        let (ctx, ()) = unsafe {
+            MainCtx::new_with_init(
+                |c, ()| {
+                    assert_eq!(cell.init(c, DropCount(1, &drops)).0, 1);
+                },
+                (),
+            )
+        };
+        drop(ctx);
+        assert_eq!(drops.get(), 0);
+
+        for i in 2..=3 {
+            let (ctx, ()) = unsafe {
+                MainCtx::new_with_init(
+                    |c, ()| {
+                        assert_eq!(cell.reinit(c, DropCount(i, &drops)).0, i);
+                    },
+                    (),
+                )
+            };
+            drop(ctx);
+            assert_eq!(drops.get(), i - 1);
+        }
+
+        let irq = unsafe { IrqCtx::new() };
+        assert_eq!(cell.as_ref_with_irqctx(&irq).0, 3);
+        drop(cell);
+        assert_eq!(drops.get(), 3);
+    }
+
+    static INIT_OR_DEFAULT_A: InitOrDefault<u16> = InitOrDefault::new();
+    static INIT_OR_DEFAULT_B: InitOrDefault<u16> = InitOrDefault::new();
+
+    #[test]
+    fn test_init_or_default() {
+        fn init(ctx: &InitCtx<'_>, _: ()) {
+            assert!(!INIT_OR_DEFAULT_A.is_initialized(ctx.cs()));
+            assert_eq!(INIT_OR_DEFAULT_A.init(ctx, 42), Ok(&42));
+            assert_eq!(INIT_OR_DEFAULT_A.init(ctx, 43), Err(43));
+            assert!(INIT_OR_DEFAULT_A.is_initialized(ctx.cs()));
+            // B is not initialized. E.g. in a safe-mode boot.
+        }
+        let (ctx, ()) = unsafe { MainCtx::new_with_init(init, ()) };
+        let irq = unsafe { IrqCtx::new() };
+
+        assert!(!INIT_OR_DEFAULT_A.is_fallback(irq.cs()));
+        assert_eq!(*INIT_OR_DEFAULT_A.as_ref_with_irqctx(&irq), 42);
+
+        assert!(!INIT_OR_DEFAULT_B.is_fallback(irq.cs()));
+        assert_eq!(*INIT_OR_DEFAULT_B.as_ref_with_irqctx(&irq), 0);
+        assert!(INIT_OR_DEFAULT_B.is_fallback(irq.cs()));
+        assert!(!INIT_OR_DEFAULT_B.is_initialized(irq.cs()));
+        assert_eq!(*INIT_OR_DEFAULT_B.as_ref_with_cs(irq.cs()), 0);
+        drop(ctx);
+
+        let local: InitOrDefault<std::vec::Vec<u8>> = InitOrDefault::default();
+        let (_ctx, ()) = unsafe {
+            MainCtx::new_with_init(
+                |c, ()| {
+                    assert!(local.as_ref_with_initctx(c).is_empty());
+                    assert_eq!(local.init(c, vec![1]), Err(vec![1]));
+                },
+                (),
+            )
+        };
+    }
+
+    #[test]
+    fn test_init_phase_token() {
+        struct TestPhase(());
+
+        fn init(ctx: &InitCtx<'_>, _: ()) -> InitPhaseToken<TestPhase> {
+            InitPhaseToken::new(ctx, TestPhase(()))
+        }
+        let (ctx, token) = unsafe { MainCtx::new_with_init(init, ()) };
+        assert_eq!(core::mem::size_of_val(&token), 0);
+        let token2 = token;
+        ctx.assert_init_done(&token);
+        ctx.assert_init_done(&token2);
+    }
+
+    #[test]
+    fn test_main_ctx_sleep() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<u16> = MainCtxCell::new(1);
+        let mut count = 0;
+        a.set(&ctx, 2);
+        ctx.sleep(|| count += 1);
+        assert_eq!(a.get(&ctx), 2);
+        a.set(&ctx, 3);
+        ctx.sleep(|| count += 1);
+        assert_eq!(a.get(&ctx), 3);
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_main_ctx_retry() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let attempts: MainCtxCell<u8> = MainCtxCell::new(0);
+        let res: Result<u8, u8> = ctx.retry(3, |m| {
+            attempts.set(m, attempts.get(m) + 1);
+            if attempts.get(m) < 3 {
+                Err(attempts.get(m))
+            } else {
+                Ok(42)
+            }
+        });
+        assert_eq!(res, Ok(42));
+        assert_eq!(attempts.get(&ctx), 3);
+
+        let mut attempts = 0;
+        let mut backoffs = vec![];
+        let res: Result<(), u8> = ctx.retry_with_backoff(
+            2,
+            |retry| backoffs.push(retry),
+            |_| {
+                attempts += 1;
+                Err(attempts)
+            },
+        );
+        assert_eq!(res, Err(3));
+        assert_eq!(backoffs, [1, 2]);
+
+        let res: Result<(), ()> = ctx.retry(0, |_| Err(()));
+        assert_eq!(res, Err(()));
+    }
+
+    #[test]
+    fn test_main_ctx_select_ready() {
+        use crate::flag::IrqFlag;
+
+        let ctx = unsafe { MainCtx::new() };
+
+        let a = IrqFlag::new();
+        let b = IrqFlag::new();
+        let c = IrqFlag::new();
+        let flags = [&a, &b, &c];
+        assert_eq!(ctx.select_ready(&flags), None);
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            c.set(&irq);
+        }
+        assert_eq!(ctx.select_ready(&flags), Some(2));
+        assert_eq!(ctx.select_ready(&flags), None);
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            b.set(&irq);
+            c.set(&irq);
+        }
+        assert_eq!(ctx.select_ready(&flags), Some(1));
+        assert_eq!(ctx.select_ready(&flags), Some(2));
+        assert_eq!(ctx.select_ready(&flags), None);
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            a.set(&irq);
+        }
+        assert_eq!(ctx.select_ready(&[]), None);
+        assert_eq!(ctx.select_ready(&[&b, &a]), Some(1));
+        assert!(!a.take(&ctx));
+    }
+
+    #[test]
+    fn test_main_ctx_batch() {
+        let ctx = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<u16> = MainCtxCell::new(1);
+        let b: MainCtxCell<u16> = MainCtxCell::new(2);
+        a.set(&ctx, 3);
+        let ret = ctx.batch(|m| {
+            assert_eq!(a.get(m), 3);
+            a.set(m, 4);
+            b.set(m, 5);
+            a.get(m) + b.get(m)
+        });
+        assert_eq!(ret, 9);
+        assert_eq!(a.get(&ctx), 4);
+        assert_eq!(b.get(&ctx), 5);
+    }
+
+    #[test]
+    fn test_main_ctx_scoped_cell() {
+        fn sum_scaled(m: &MainCtx<'_>, values: &[u16], scale: &MainCtxCell<u16>) -> u16 {
+            m.scoped_cell(0_u16, |sum| {
+                for v in values {
+                    sum.set(m, sum.get(m) + *v * scale.get(m));
+                }
+                sum.get(m)
+            })
+        }
+
+        let ctx = unsafe { MainCtx::new() };
+
+        let scale: MainCtxCell<u16> = MainCtxCell::new(2);
+        assert_eq!(sum_scaled(&ctx, &[1, 2, 3], &scale), 12);
+        assert_eq!(sum_scaled(&ctx, &[], &scale), 0);
+    }
+
+    static INIT_CHAIN_A: InitCtxCell<u16> = unsafe { InitCtxCell::uninit() };
+    static INIT_CHAIN_B: InitCtxCell<u32> = unsafe { InitCtxCell::uninit() };
+    static INIT_CHAIN_C: InitCtxCell<[u32; 2]> = unsafe { InitCtxCell::uninit() };
+
+    #[test]
+    fn test_init_ctx_init_from() {
+        fn init(ctx: &InitCtx<'_>, _: ()) {
+            INIT_CHAIN_A.init(ctx, 10);
+            let b = INIT_CHAIN_B.init_from(ctx, &INIT_CHAIN_A, |a| *a as u32 * 2);
+            assert_eq!(*b, 20);
+            let c = INIT_CHAIN_C.init_from(ctx, &INIT_CHAIN_B, |b| [*b, *b + 1]);
+            assert_eq!(*c, [20, 21]);
+        }
+        let (ctx, ()) = unsafe { MainCtx::new_with_init(init, ()) };
+        let irq = unsafe { IrqCtx::new() };
+        assert_eq!(*INIT_CHAIN_A.as_ref_with_irqctx(&irq), 10);
+        assert_eq!(*INIT_CHAIN_B.as_ref_with_irqctx(&irq), 20);
+        assert_eq!(*INIT_CHAIN_C.as_ref_with_irqctx(&irq), [20, 21]);
+        drop(ctx);
+    }
+
+    static DMA_TABLE: InitCtxCell<[u8; 4]> = unsafe { InitCtxCell::uninit() };
+
+    #[test]
+    fn test_typed_config() {
+        crate::typed_config! {
+            #[derive(Default)]
+            struct Config {
+                baud: u32,
+                name: [u8; 3],
+            }
+            static CONFIG;
+        }
+
+        crate::typed_config! {
+            #[derive(Default)]
+            struct Fallback {
+                retries: u8,
+            }
+            static FALLBACK;
+        }
+
+        fn init(ctx: &InitCtx<'_>, baud: u32) -> u32 {
+            let config = Config::init(
+                ctx,
+                Config {
+                    baud,
+                    name: *b"abc",
+                },
+            );
+            assert!(Config::init(ctx, Config::default()).is_err());
+            config.map_or(0, |c| c.baud)
+        }
+        let (ctx, ret) = unsafe { MainCtx::new_with_init(init, 19200) };
+        assert_eq!(ret, 19200);
+        assert_eq!(*Config::baud(&ctx), 19200);
+        assert_eq!(Config::name(&ctx), b"abc");
+        assert_eq!(Config::get(&ctx).name[1], b'b');
+        let irq = unsafe { IrqCtx::new() };
+        assert_eq!(CONFIG.as_ref_with_irqctx(&irq).baud, 19200);
+
+        // Not initialized. Falls back to the default configuration.
+        assert_eq!(*Fallback::retries(&ctx), 0);
+        assert!(FALLBACK.is_fallback(irq.cs()));
+    }
+
+    #[test]
+    fn test_init_ctx_cell_ptr() {
+        fn init(ctx: &InitCtx<'_>, _: ()) -> *const [u8; 4] {
+            let ptr = DMA_TABLE.as_ptr(ctx);
+            DMA_TABLE.init(ctx, [1, 2, 3, 4]);
+            assert_eq!(DMA_TABLE.as_ptr(ctx), ptr);
+            let mut_ptr = DMA_TABLE.as_mut_ptr(ctx);
+            assert_eq!(mut_ptr as *const _, ptr);
+            unsafe { (*mut_ptr)[3] = 5 };
+            assert_eq!(unsafe { *ptr }, [1, 2, 3, 5]);
+            ptr
+        }
+        let (ctx, ptr) = unsafe { MainCtx::new_with_init(init, ()) };
+        let irq = unsafe { IrqCtx::new() };
+        let inner = DMA_TABLE.as_ref_with_irqctx(&irq);
+        assert_eq!(inner as *const _, ptr);
+        assert_eq!(*inner, [1, 2, 3, 5]);
+        drop(ctx);
+    }
+
+    #[test]
+    fn test_init_ctx_with_interrupts() {
+        use std::cell::RefCell;
+
+        fn init(ctx: &InitCtx<'_>, _: ()) -> Vec<&'static str> {
+            let log = RefCell::new(vec![]);
+            let ret = unsafe {
+                ctx.with_interrupts(
+                    || log.borrow_mut().push("enable"),
+                    || log.borrow_mut().push("disable"),
+                    || {
+                        log.borrow_mut().push("f");
+                        42
+                    },
+                )
+            };
+            assert_eq!(ret, 42);
+            let _: CriticalSection<'_> = ctx.cs();
+            log.into_inner()
+        }
+        let (_ctx, log) = unsafe { MainCtx::new_with_init(init, ()) };
+        assert_eq!(log, ["enable", "f", "disable"]);
+    }
+
+    #[test]
+    fn test_ctx_id() {
+        fn leaf(m: &MainCtx<'_>) -> CtxId {
+            m.id()
+        }
+        fn middle(m: &MainCtx<'_>) -> CtxId {
+            leaf(m)
+        }
+        fn irq_leaf(irq: &IrqCtx<'_>) -> CtxId {
+            irq.id()
+        }
+
+        let a = unsafe { MainCtx::new() };
+        let b = unsafe { MainCtx::new() };
+        assert_eq!(middle(&a), a.id());
+        assert_eq!(middle(&b), b.id());
+
+        let irq = unsafe { IrqCtx::new() };
+        assert_eq!(irq_leaf(&irq), irq.id());
+
+        if cfg!(debug_assertions) {
+            assert_ne!(a.id(), b.id());
+            assert_ne!(middle(&a), irq.id());
+        } else {
+            assert_eq!(a.id(), b.id());
+            assert_eq!(core::mem::size_of::<MainCtx<'_>>(), 0);
+        }
+    }
+
     #[test]
     fn test_irq_ctx() {
         let ctx = unsafe { IrqCtx::new() };
         let _: CriticalSection<'_> = ctx.cs();
     }
+
+    #[test]
+    fn test_irq_ctx_ffi() {
+        extern "C" fn callback(ctx: FfiCtx) -> u16 {
+            let irq = unsafe { IrqCtx::from_ffi(ctx) };
+            IRQ_FFI_CELL.replace(&irq, 2)
+        }
+
+        static IRQ_FFI_CELL: IrqCtxCell<u16> = IrqCtxCell::new(1);
+
+        let irq = unsafe { IrqCtx::new() };
+        let ffi = irq.to_ffi();
+        assert_eq!(
+            core::mem::size_of_val(&ffi),
+            core::mem::size_of::<*const ()>()
+        );
+        assert_eq!(callback(ffi), 1);
+    }
+
+    static IRQ_CELL: IrqCtxCell<[u16; 3]> = IrqCtxCell::new([0; 3]);
+
+    #[test]
+    fn test_irq_ctx_cell() {
+        fn init(ctx: &InitCtx<'_>, _: ()) {
+            IRQ_CELL.init_bulk(ctx, &[1, 2, 3]);
+        }
+        let (_ctx, ()) = unsafe { MainCtx::new_with_init(init, ()) };
+
+        let irq = unsafe { IrqCtx::new() };
+        assert_eq!(IRQ_CELL.get(&irq), [1, 2, 3]);
+        IRQ_CELL.set(&irq, [4, 5, 6]);
+        assert_eq!(IRQ_CELL.replace(&irq, [7, 8, 9]), [4, 5, 6]);
+        assert_eq!(IRQ_CELL.get(&irq), [7, 8, 9]);
+    }
+
+    #[test]
+    #[should_panic(expected = "values must have length N")]
+    fn test_irq_ctx_cell_init_bulk_len() {
+        fn init(ctx: &InitCtx<'_>, _: ()) {
+            let cell: IrqCtxCell<[u16; 3]> = IrqCtxCell::new([0; 3]);
+            cell.init_bulk(ctx, &[1, 2]);
+        }
+        let _ = unsafe { MainCtx::new_with_init(init, ()) };
+    }
+
+    #[test]
+    fn test_irq_ctx_replace() {
+        let ctx = unsafe { IrqCtx::new() };
+
+        let shared: Mutex<core::cell::Cell<u16>> = Mutex::new(core::cell::Cell::new(1));
+        assert_eq!(ctx.replace(&shared, 2), 1);
+        assert_eq!(ctx.replace(&shared, 3), 2);
+        assert_eq!(shared.borrow(ctx.cs()).get(), 3);
+    }
+
+    #[test]
+    fn test_irq_ctx_cell_with_mut() {
+        struct IsrState {
+            count: u16,
+            samples: [u8; 4],
+        }
+
+        fn isr(c: &mut IrqCtx<'_>, cell: &IrqCtxCell<IsrState>, sample: u8) -> u16 {
+            cell.with_mut(c, |state| {
+                state.samples[state.count as usize % 4] = sample;
+                state.count += 1;
+                state.count
+            })
+        }
+
+        let mut ctx = unsafe { IrqCtx::new() };
+
+        let cell = IrqCtxCell::new(IsrState {
+            count: 0,
+            samples: [0; 4],
+        });
+        assert_eq!(isr(&mut ctx, &cell, 10), 1);
+        assert_eq!(isr(&mut ctx, &cell, 20), 2);
+        cell.with_mut(&mut ctx, |state| {
+            assert_eq!(state.count, 2);
+            assert_eq!(state.samples, [10, 20, 0, 0]);
+        });
+    }
+
+    #[cfg(feature = "avr-hal")]
+    #[test]
+    fn test_irq_ctx_in_isr() {
+        #[allow(non_snake_case)]
+        fn TIMER1_COMPA(cell: &IrqCtxCell<u8>) {
+            let c = unsafe { IrqCtx::in_isr() };
+            cell.set(&c, cell.get(&c) + 1);
+        }
+
+        let cell = IrqCtxCell::new(1);
+        TIMER1_COMPA(&cell);
+        let c = unsafe { IrqCtx::new() };
+        assert_eq!(cell.get(&c), 2);
+    }
+
+    #[test]
+    fn test_irq_ctx_bump() {
+        let ctx = unsafe { IrqCtx::new() };
+
+        let overflows: IrqCtxCell<u16> = IrqCtxCell::new(u16::MAX - 2);
+        assert!(!ctx.bump(&overflows));
+        assert!(!ctx.bump(&overflows));
+        assert_eq!(overflows.get(&ctx), u16::MAX);
+        assert!(ctx.bump(&overflows));
+        assert_eq!(overflows.get(&ctx), 0);
+        assert!(!ctx.bump(&overflows));
+        assert_eq!(overflows.get(&ctx), 1);
+    }
+
+    #[test]
+    fn test_irq_ctx_nesting() {
+        IrqCtx::reset_max_observed();
+        assert_eq!(IrqCtx::nesting_depth(), 0);
+        {
+            let _outer = unsafe { IrqCtx::new() };
+            {
+                let _inner = unsafe { IrqCtx::new() };
+                {
+                    let _innermost: TaggedIrqCtx<'_, ()> = unsafe { TaggedIrqCtx::new() };
+                    if cfg!(debug_assertions) {
+                        assert_eq!(IrqCtx::nesting_depth(), 3);
+                    }
+                }
+            }
+            let _inner = unsafe { IrqCtx::new() };
+            if cfg!(debug_assertions) {
+                assert_eq!(IrqCtx::nesting_depth(), 2);
+            }
+        }
+        assert_eq!(IrqCtx::nesting_depth(), 0);
+        if cfg!(debug_assertions) {
+            assert_eq!(IrqCtx::max_observed(), 3);
+        } else {
+            assert_eq!(IrqCtx::max_observed(), 0);
+        }
+        IrqCtx::reset_max_observed();
+        assert_eq!(IrqCtx::max_observed(), 0);
+    }
+
+    #[test]
+    fn test_irq_ctx_fetch_or_and() {
+        let ctx = unsafe { IrqCtx::new() };
+
+        let status: IrqCtxCell<u8> = IrqCtxCell::new(0);
+        assert_eq!(ctx.fetch_or(&status, 0x01), 0x00);
+        assert_eq!(ctx.fetch_or(&status, 0x81), 0x01);
+        assert_eq!(status.get(&ctx), 0x81);
+        assert_eq!(ctx.fetch_and(&status, !0x01), 0x81);
+        assert_eq!(status.get(&ctx), 0x80);
+        assert_eq!(ctx.fetch_and(&status, 0x00), 0x80);
+        assert_eq!(status.get(&ctx), 0x00);
+    }
+
+    struct TimerTag;
+    struct UartTag;
+
+    static TIMER_TICKS: TaggedIrqCtxCell<TimerTag, u16> = TaggedIrqCtxCell::new(0);
+    static UART_BYTE: TaggedIrqCtxCell<UartTag, u8> = TaggedIrqCtxCell::new(0);
+
+    #[test]
+    fn test_tagged_irq_ctx() {
+        fn init(ctx: &InitCtx<'_>, _: ()) {
+            TIMER_TICKS.init(ctx, 10);
+        }
+        let _ = unsafe { MainCtx::new_with_init(init, ()) };
+
+        fn timer_isr(c: &TaggedIrqCtx<'_, TimerTag>) {
+            let ticks = TIMER_TICKS.get(c);
+            TIMER_TICKS.set(c, ticks + 1);
+        }
+        fn uart_isr(c: &TaggedIrqCtx<'_, UartTag>) {
+            assert_eq!(UART_BYTE.replace(c, 0x55), 0);
+        }
+
+        let timer = unsafe { TaggedIrqCtx::<TimerTag>::new() };
+        timer_isr(&timer);
+        timer_isr(&timer);
+        assert_eq!(TIMER_TICKS.get(&timer), 12);
+
+        let uart = unsafe { TaggedIrqCtx::<UartTag>::new() };
+        uart_isr(&uart);
+        assert_eq!(UART_BYTE.get(&uart), 0x55);
+        assert_ne!(uart.id(), timer.id());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_main_ctx_access() {
+        #[derive(MainCtxAccess)]
+        struct DeviceState {
+            speed: MainCtxCell<u16>,
+            mode: cell::MainCtxCell<[u8; 2]>,
+            name: &'static str,
+        }
+
+        let ctx = unsafe { MainCtx::new() };
+
+        let state = DeviceState {
+            speed: MainCtxCell::new(1),
+            mode: MainCtxCell::new([2, 3]),
+            name: "dev",
+        };
+        assert_eq!(state.speed(&ctx), 1);
+        state.set_speed(&ctx, 4);
+        assert_eq!(state.speed(&ctx), 4);
+        assert_eq!(state.mode(&ctx), [2, 3]);
+        state.set_mode(&ctx, [5, 6]);
+        assert_eq!(state.mode.get(&ctx), [5, 6]);
+        assert_eq!(state.name, "dev");
+    }
 }
 
 // vim: ts=4 sw=4 expandtab
@@ -0,0 +1,186 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Fixed capacity least recently used cache in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// Cache with up to `N` entries that can only be accessed from `main()` context.
+///
+/// If the cache is full, inserting a new entry evicts the least recently used entry.
+/// Both [Self::get] and [Self::put] mark the entry as most recently used.
+///
+/// The entries are kept in order of use and lookups are linear searches.
+/// This is intended for small caches, e.g. of recently seen device addresses.
+pub struct LruCache<K, V, const N: usize> {
+    /// Entries ordered from most recently used to least recently used.
+    entries: [MainCtxCell<Option<(K, V)>>; N],
+}
+
+impl<K: Copy + Eq, V: Copy, const N: usize> LruCache<K, V, N> {
+    /// Create a new empty cache.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            entries: [const { MainCtxCell::new(None) }; N],
+        }
+    }
+
+    /// Find the index of the entry with the `key`.
+    #[inline(always)]
+    fn find(&self, m: &MainCtx<'_>, key: &K) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.get(m).is_some_and(|(k, _)| k == *key))
+    }
+
+    /// Move the entry at `index` to the front.
+    #[inline(always)]
+    fn promote(&self, m: &MainCtx<'_>, index: usize) {
+        let entry = self.entries[index].get(m);
+        for i in (0..index).rev() {
+            self.entries[i + 1].set(m, self.entries[i].get(m));
+        }
+        self.entries[0].set(m, entry);
+    }
+
+    /// Get the value of `key` and mark it as most recently used.
+    ///
+    /// Returns `None`, if the `key` is not in the cache.
+    #[inline(always)]
+    pub fn get(&self, m: &MainCtx<'_>, key: &K) -> Option<V> {
+        let index = self.find(m, key)?;
+        self.promote(m, index);
+        self.entries[0].get(m).map(|(_, v)| v)
+    }
+
+    /// Get the value of `key` without changing the order of use.
+    ///
+    /// Returns `None`, if the `key` is not in the cache.
+    #[inline(always)]
+    pub fn peek(&self, m: &MainCtx<'_>, key: &K) -> Option<V> {
+        let index = self.find(m, key)?;
+        self.entries[index].get(m).map(|(_, v)| v)
+    }
+
+    /// Insert or update the `value` of `key` and mark it as most recently used.
+    ///
+    /// Returns the evicted least recently used entry, if the cache was full.
+    /// If `N` is 0, the new entry itself is returned.
+    #[inline(always)]
+    pub fn put(&self, m: &MainCtx<'_>, key: K, value: V) -> Option<(K, V)> {
+        if N == 0 {
+            return Some((key, value));
+        }
+        let (index, evicted) = match self.find(m, &key) {
+            Some(index) => (index, None),
+            None => (N - 1, self.entries[N - 1].get(m)),
+        };
+        self.entries[index].set(m, Some((key, value)));
+        self.promote(m, index);
+        evicted
+    }
+
+    /// Remove the entry of `key` and return its value.
+    ///
+    /// Returns `None`, if the `key` is not in the cache.
+    #[inline(always)]
+    pub fn remove(&self, m: &MainCtx<'_>, key: &K) -> Option<V> {
+        let index = self.find(m, key)?;
+        let entry = self.entries[index].get(m);
+        for i in index..N - 1 {
+            self.entries[i].set(m, self.entries[i + 1].get(m));
+        }
+        self.entries[N - 1].set(m, None);
+        entry.map(|(_, v)| v)
+    }
+
+    /// Remove all entries.
+    #[inline(always)]
+    pub fn clear(&self, m: &MainCtx<'_>) {
+        for entry in &self.entries {
+            entry.set(m, None);
+        }
+    }
+
+    /// Get the number of entries in the cache.
+    #[inline(always)]
+    pub fn len(&self, m: &MainCtx<'_>) -> usize {
+        self.entries.iter().filter(|e| e.get(m).is_some()).count()
+    }
+
+    /// Returns `true`, if the cache is empty.
+    #[inline(always)]
+    pub fn is_empty(&self, m: &MainCtx<'_>) -> bool {
+        self.len(m) == 0
+    }
+
+    /// Get the maximum number of entries in the cache.
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<K: Copy + Eq, V: Copy, const N: usize> Default for LruCache<K, V, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lru_cache() {
+        let m = unsafe { MainCtx::new() };
+
+        let cache: LruCache<u8, u16, 3> = LruCache::new();
+        assert_eq!(cache.capacity(), 3);
+        assert!(cache.is_empty(&m));
+        assert_eq!(cache.get(&m, &1), None);
+
+        assert_eq!(cache.put(&m, 1, 100), None);
+        assert_eq!(cache.put(&m, 2, 200), None);
+        assert_eq!(cache.put(&m, 3, 300), None);
+        assert_eq!(cache.len(&m), 3);
+
+        // 1 is the least recently used entry.
+        assert_eq!(cache.put(&m, 4, 400), Some((1, 100)));
+        assert_eq!(cache.get(&m, &1), None);
+
+        // Using 2 makes 3 the least recently used entry.
+        assert_eq!(cache.get(&m, &2), Some(200));
+        assert_eq!(cache.put(&m, 5, 500), Some((3, 300)));
+
+        // Peeking doesn't change the order. 4 is evicted.
+        assert_eq!(cache.peek(&m, &4), Some(400));
+        assert_eq!(cache.put(&m, 6, 600), Some((4, 400)));
+
+        // Updating an entry doesn't evict and marks it as most recently used.
+        assert_eq!(cache.put(&m, 2, 201), None);
+        assert_eq!(cache.len(&m), 3);
+        assert_eq!(cache.put(&m, 7, 700), Some((5, 500)));
+        assert_eq!(cache.get(&m, &2), Some(201));
+
+        assert_eq!(cache.remove(&m, &6), Some(600));
+        assert_eq!(cache.remove(&m, &6), None);
+        assert_eq!(cache.len(&m), 2);
+        assert_eq!(cache.put(&m, 8, 800), None);
+        assert_eq!(cache.put(&m, 9, 900), Some((7, 700)));
+
+        cache.clear(&m);
+        assert!(cache.is_empty(&m));
+
+        let empty: LruCache<u8, u8, 0> = LruCache::new();
+        assert_eq!(empty.put(&m, 1, 2), Some((1, 2)));
+        assert_eq!(empty.get(&m, &1), None);
+        assert_eq!(empty.remove(&m, &1), None);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
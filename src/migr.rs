@@ -0,0 +1,154 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Migration aid from atomic globals to [MainCtxCell](crate::MainCtxCell).
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+use core::sync::atomic::Ordering;
+
+/// Cell with an atomic-like API that can only be accessed from `main()` context.
+///
+/// This type helps to migrate globals like `AtomicU8`, that are only used from `main()`,
+/// to [MainCtxCell].
+/// Replace the atomic type by `MigrAtomic` and add the `&MainCtx` argument to the accesses.
+/// Then replace the `MigrAtomic` by `MainCtxCell` and drop the `Ordering` arguments.
+///
+/// The `Ordering` arguments are ignored.
+/// There is only one `main()` context and no interrupt can access the cell.
+/// Therefore, all accesses are sequentially consistent anyway.
+///
+/// Like the atomic types, arithmetic wraps around on overflow.
+pub struct MigrAtomic<T> {
+    inner: MainCtxCell<T>,
+}
+
+impl<T: Copy> MigrAtomic<T> {
+    /// Create a new cell with the `inner` value.
+    #[inline(always)]
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner: MainCtxCell::new(inner),
+        }
+    }
+
+    /// Load the value.
+    ///
+    /// The `Ordering` is ignored.
+    #[inline(always)]
+    pub fn load(&self, m: &MainCtx<'_>, _: Ordering) -> T {
+        self.inner.get(m)
+    }
+
+    /// Store the value.
+    ///
+    /// The `Ordering` is ignored.
+    #[inline(always)]
+    pub fn store(&self, m: &MainCtx<'_>, val: T, _: Ordering) {
+        self.inner.set(m, val);
+    }
+
+    /// Store the value and return the previous value.
+    ///
+    /// The `Ordering` is ignored.
+    #[inline(always)]
+    pub fn swap(&self, m: &MainCtx<'_>, val: T, _: Ordering) -> T {
+        self.inner.replace(m, val)
+    }
+
+    /// Get the underlying [MainCtxCell].
+    #[inline(always)]
+    pub fn as_cell(&self) -> &MainCtxCell<T> {
+        &self.inner
+    }
+
+    /// Convert into the underlying [MainCtxCell].
+    #[inline(always)]
+    pub fn into_cell(self) -> MainCtxCell<T> {
+        self.inner
+    }
+}
+
+macro_rules! impl_migr_atomic_int {
+    ($($ty:ty),*) => {
+        $(
+            impl MigrAtomic<$ty> {
+                /// Add to the value and return the previous value.
+                ///
+                /// The `Ordering` is ignored.
+                #[inline(always)]
+                pub fn fetch_add(&self, m: &MainCtx<'_>, val: $ty, _: Ordering) -> $ty {
+                    let old = self.inner.get(m);
+                    self.inner.set(m, old.wrapping_add(val));
+                    old
+                }
+
+                /// Subtract from the value and return the previous value.
+                ///
+                /// The `Ordering` is ignored.
+                #[inline(always)]
+                pub fn fetch_sub(&self, m: &MainCtx<'_>, val: $ty, _: Ordering) -> $ty {
+                    let old = self.inner.get(m);
+                    self.inner.set(m, old.wrapping_sub(val));
+                    old
+                }
+            }
+        )*
+    };
+}
+
+impl_migr_atomic_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl<T: Copy + Default> Default for MigrAtomic<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use core::sync::atomic::AtomicU8;
+
+    #[test]
+    fn test_migr_atomic() {
+        let m = unsafe { MainCtx::new() };
+
+        let atomic = AtomicU8::new(250);
+        let migr: MigrAtomic<u8> = MigrAtomic::new(250);
+
+        assert_eq!(atomic.load(Ordering::Relaxed), 250);
+        assert_eq!(migr.load(&m, Ordering::Relaxed), 250);
+
+        assert_eq!(atomic.fetch_add(10, Ordering::SeqCst), 250);
+        assert_eq!(migr.fetch_add(&m, 10, Ordering::SeqCst), 250);
+        assert_eq!(atomic.load(Ordering::Acquire), 4);
+        assert_eq!(migr.load(&m, Ordering::Acquire), 4);
+
+        assert_eq!(atomic.fetch_sub(5, Ordering::AcqRel), 4);
+        assert_eq!(migr.fetch_sub(&m, 5, Ordering::AcqRel), 4);
+        assert_eq!(atomic.load(Ordering::Relaxed), 255);
+        assert_eq!(migr.load(&m, Ordering::Relaxed), 255);
+
+        atomic.store(7, Ordering::Release);
+        migr.store(&m, 7, Ordering::Release);
+        assert_eq!(atomic.swap(8, Ordering::SeqCst), 7);
+        assert_eq!(migr.swap(&m, 8, Ordering::SeqCst), 7);
+
+        assert_eq!(migr.as_cell().get(&m), 8);
+        assert_eq!(migr.into_cell().get(&m), 8);
+    }
+
+    #[test]
+    fn test_migr_atomic_signed() {
+        let m = unsafe { MainCtx::new() };
+
+        let migr: MigrAtomic<i16> = MigrAtomic::default();
+        assert_eq!(migr.fetch_sub(&m, 1, Ordering::Relaxed), 0);
+        assert_eq!(migr.fetch_add(&m, i16::MAX, Ordering::Relaxed), -1);
+        assert_eq!(migr.load(&m, Ordering::Relaxed), i16::MAX - 1);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
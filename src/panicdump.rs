@@ -0,0 +1,156 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Dumping of registered `main()` context state from a panic handler.
+//!
+//! This module is only available with the `panic-dump` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use avr_context::{MainCtx, MainCtxCell, panicdump::PanicDump};
+//!
+//! static SPEED: MainCtxCell<u16> = MainCtxCell::new(0);
+//! static STATE: MainCtxCell<u8> = MainCtxCell::new(0);
+//! static DUMP: PanicDump<2> = PanicDump::new();
+//!
+//! fn init(m: &MainCtx<'_>) {
+//!     DUMP.register(m, 0, "speed", &SPEED);
+//!     DUMP.register(m, 1, "state", &STATE);
+//! }
+//!
+//! // Call this from the `#[panic_handler]` with interrupts disabled.
+//! fn on_panic(out: &mut dyn core::fmt::Write) {
+//!     // SAFETY: Interrupts are disabled and main() does not run anymore.
+//!     let _ = unsafe { DUMP.dump(out) };
+//! }
+//! ```
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+use core::fmt;
+
+/// A cell whose contents can be dumped as text.
+pub trait DumpCell: Sync {
+    /// Write the current contents of the cell to `out`.
+    fn dump(&self, m: &MainCtx<'_>, out: &mut dyn fmt::Write) -> fmt::Result;
+}
+
+impl<T: Copy + Send + fmt::Debug> DumpCell for MainCtxCell<T> {
+    #[inline(always)]
+    fn dump(&self, m: &MainCtx<'_>, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(out, "{:?}", self.get(m))
+    }
+}
+
+type Entry = (&'static str, &'static dyn DumpCell);
+
+/// Registry of up to `N` named cells that are dumped on panic.
+///
+/// The cells are registered from `main()` context.
+/// The panic handler then writes all registered cells
+/// with [Self::dump] for crash diagnostics.
+pub struct PanicDump<const N: usize> {
+    entries: [MainCtxCell<Option<Entry>>; N],
+}
+
+impl<const N: usize> PanicDump<N> {
+    /// Create a new registry without cells.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            entries: [const { MainCtxCell::new(None) }; N],
+        }
+    }
+
+    /// Register the `cell` with the given `name` as entry `id`.
+    ///
+    /// A previously registered cell with the same `id` is replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is not smaller than `N`.
+    #[inline(always)]
+    pub fn register(
+        &self,
+        m: &MainCtx<'_>,
+        id: usize,
+        name: &'static str,
+        cell: &'static dyn DumpCell,
+    ) {
+        self.entries[id].set(m, Some((name, cell)));
+    }
+
+    /// Remove the entry `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is not smaller than `N`.
+    #[inline(always)]
+    pub fn unregister(&self, m: &MainCtx<'_>, id: usize) {
+        self.entries[id].set(m, None);
+    }
+
+    /// Write all registered cells to `out`.
+    ///
+    /// Each cell is written as one `name=value` line.
+    ///
+    /// # Safety
+    ///
+    /// This accesses the registered `main()` context cells without a [MainCtx].
+    /// It must only be called from the panic handler with interrupts disabled,
+    /// where no other code can access the cells anymore.
+    pub unsafe fn dump(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        // SAFETY: Nothing else runs. See the safety contract of this function.
+        let m = unsafe { MainCtx::new() };
+        for entry in &self.entries {
+            if let Some((name, cell)) = entry.get(&m) {
+                write!(out, "{name}=")?;
+                cell.dump(&m, out)?;
+                writeln!(out)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for PanicDump<N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use std::string::String;
+
+    static A: MainCtxCell<u16> = MainCtxCell::new(0);
+    static B: MainCtxCell<Option<i8>> = MainCtxCell::new(None);
+
+    #[test]
+    fn test_panic_dump() {
+        let m = unsafe { MainCtx::new() };
+
+        let dump: PanicDump<3> = PanicDump::new();
+        let mut out = String::new();
+        unsafe { dump.dump(&mut out) }.unwrap();
+        assert!(out.is_empty());
+
+        dump.register(&m, 2, "a", &A);
+        dump.register(&m, 0, "b", &B);
+        A.set(&m, 1234);
+        B.set(&m, Some(-5));
+        unsafe { dump.dump(&mut out) }.unwrap();
+        assert_eq!(out, "b=Some(-5)\na=1234\n");
+
+        dump.unregister(&m, 0);
+        out.clear();
+        unsafe { dump.dump(&mut out) }.unwrap();
+        assert_eq!(out, "a=1234\n");
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
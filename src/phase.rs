@@ -0,0 +1,121 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Runtime phase tracking in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// The current runtime phase of the firmware that can only be accessed from `main()` context.
+///
+/// `E` is typically a user defined enum of phases,
+/// for example calibrating, running and fault.
+///
+/// Subsystem functions that are only valid in a certain phase
+/// can check the phase before operating.
+pub struct Phase<E> {
+    phase: MainCtxCell<E>,
+}
+
+impl<E: Copy> Phase<E> {
+    /// Create a new phase tracker that starts in the `initial` phase.
+    #[inline(always)]
+    pub const fn new(initial: E) -> Self {
+        Self {
+            phase: MainCtxCell::new(initial),
+        }
+    }
+
+    /// Get the current phase.
+    #[inline(always)]
+    pub fn get(&self, m: &MainCtx<'_>) -> E {
+        self.phase.get(m)
+    }
+
+    /// Switch to the `phase`.
+    #[inline(always)]
+    pub fn set(&self, m: &MainCtx<'_>, phase: E) {
+        self.phase.set(m, phase);
+    }
+}
+
+impl<E: Copy + PartialEq> Phase<E> {
+    /// Returns `true`, if the current phase is `expected`.
+    #[inline(always)]
+    pub fn is(&self, m: &MainCtx<'_>, expected: E) -> bool {
+        self.get(m) == expected
+    }
+
+    /// Check that the current phase is `expected`.
+    ///
+    /// Returns the current phase as error, if it is not `expected`.
+    #[inline(always)]
+    pub fn check(&self, m: &MainCtx<'_>, expected: E) -> Result<(), E> {
+        let phase = self.get(m);
+        if phase == expected {
+            Ok(())
+        } else {
+            Err(phase)
+        }
+    }
+
+    /// Assert that the current phase is `expected`.
+    ///
+    /// This panics in debug builds, if the current phase is not `expected`.
+    /// This does nothing in release builds.
+    /// Use [Self::check] to handle a wrong phase at runtime.
+    #[inline(always)]
+    pub fn assert_phase(&self, m: &MainCtx<'_>, expected: E) {
+        debug_assert!(self.is(m, expected), "Phase: wrong runtime phase");
+    }
+}
+
+impl<E: Copy + Default> Default for Phase<E> {
+    fn default() -> Self {
+        Self::new(E::default())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Mode {
+        Calibrating,
+        Running,
+        Fault,
+    }
+
+    #[test]
+    fn test_phase() {
+        let m = unsafe { MainCtx::new() };
+
+        let phase = Phase::new(Mode::Calibrating);
+        assert_eq!(phase.get(&m), Mode::Calibrating);
+        assert!(phase.is(&m, Mode::Calibrating));
+        assert_eq!(phase.check(&m, Mode::Calibrating), Ok(()));
+        phase.assert_phase(&m, Mode::Calibrating);
+
+        assert!(!phase.is(&m, Mode::Running));
+        assert_eq!(phase.check(&m, Mode::Running), Err(Mode::Calibrating));
+
+        phase.set(&m, Mode::Running);
+        assert_eq!(phase.check(&m, Mode::Running), Ok(()));
+        phase.assert_phase(&m, Mode::Running);
+        assert_eq!(phase.check(&m, Mode::Fault), Err(Mode::Running));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "wrong runtime phase")]
+    fn test_phase_assert_wrong() {
+        let m = unsafe { MainCtx::new() };
+
+        let phase = Phase::new(Mode::Fault);
+        phase.assert_phase(&m, Mode::Running);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
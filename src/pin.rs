@@ -0,0 +1,114 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! GPIO output pin state.
+
+use crate::{context::MainCtx, regshadow::RegShadow};
+
+/// Shadowed state of a GPIO output pin that can only be accessed from `main()` context.
+///
+/// This is a [RegShadow] of the pin level.
+/// All modifications update the shadow state and then write
+/// the new state to the hardware with the user provided `writer` function.
+/// `true` is the high level and `false` is the low level.
+pub struct PinState {
+    shadow: RegShadow<bool>,
+}
+
+impl PinState {
+    /// Create a new pin state with the `initial` level.
+    ///
+    /// `writer` is called to write a new level to the hardware pin.
+    /// The `initial` level is not written to the hardware.
+    #[inline(always)]
+    pub const fn new(initial: bool, writer: fn(bool)) -> Self {
+        Self {
+            shadow: RegShadow::new(initial, writer),
+        }
+    }
+
+    /// Get the shadow state of the pin.
+    #[inline(always)]
+    pub fn is_high(&self, m: &MainCtx<'_>) -> bool {
+        self.shadow.read(m)
+    }
+
+    /// Get the inverted shadow state of the pin.
+    #[inline(always)]
+    pub fn is_low(&self, m: &MainCtx<'_>) -> bool {
+        !self.is_high(m)
+    }
+
+    /// Write the `high` level to the shadow state and to the hardware pin.
+    #[inline(always)]
+    pub fn set(&self, m: &MainCtx<'_>, high: bool) {
+        self.shadow.write(m, high);
+    }
+
+    /// Set the pin to high level.
+    #[inline(always)]
+    pub fn set_high(&self, m: &MainCtx<'_>) {
+        self.set(m, true);
+    }
+
+    /// Set the pin to low level.
+    #[inline(always)]
+    pub fn set_low(&self, m: &MainCtx<'_>) {
+        self.set(m, false);
+    }
+
+    /// Invert the pin level.
+    #[inline(always)]
+    pub fn toggle(&self, m: &MainCtx<'_>) {
+        self.set(m, !self.is_high(m));
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed};
+
+    static MOCK_PIN: AtomicBool = AtomicBool::new(false);
+    static MOCK_WRITES: AtomicUsize = AtomicUsize::new(0);
+
+    fn write_mock_pin(high: bool) {
+        MOCK_PIN.store(high, Relaxed);
+        MOCK_WRITES.fetch_add(1, Relaxed);
+    }
+
+    #[test]
+    fn test_pin_state() {
+        let m = unsafe { MainCtx::new() };
+
+        let pin = PinState::new(true, write_mock_pin);
+        assert!(pin.is_high(&m));
+        assert!(!pin.is_low(&m));
+        assert_eq!(MOCK_WRITES.load(Relaxed), 0);
+
+        pin.set_low(&m);
+        assert!(pin.is_low(&m));
+        assert!(!MOCK_PIN.load(Relaxed));
+
+        pin.toggle(&m);
+        assert!(pin.is_high(&m));
+        assert!(MOCK_PIN.load(Relaxed));
+
+        pin.toggle(&m);
+        assert!(pin.is_low(&m));
+        assert!(!MOCK_PIN.load(Relaxed));
+
+        pin.set_high(&m);
+        assert!(pin.is_high(&m));
+        assert!(MOCK_PIN.load(Relaxed));
+
+        pin.set(&m, true);
+        assert!(pin.is_high(&m));
+        assert!(MOCK_PIN.load(Relaxed));
+        assert_eq!(MOCK_WRITES.load(Relaxed), 5);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
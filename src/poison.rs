@@ -0,0 +1,209 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Poisoning of critical state in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// What a read of a poisoned [Poisonable] does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoisonPolicy<T> {
+    /// Panic on read.
+    Panic,
+    /// Return this safe default value instead of the stored value.
+    Fallback(T),
+}
+
+/// Critical state that can be poisoned when an invariant violation has been detected.
+///
+/// After [Self::poison] has been called, reads through [Self::get]
+/// behave as configured by the [PoisonPolicy],
+/// until the state is recovered with [Self::recover].
+///
+/// # Example
+///
+/// ```
+/// use avr_context::{
+///     MainCtx,
+///     errors::ErrorSink,
+///     poison::{PoisonPolicy, Poisonable},
+///     soft_assert,
+/// };
+///
+/// static ERRORS: ErrorSink = ErrorSink::new();
+/// static SPEED: Poisonable<u16> = Poisonable::new(0, PoisonPolicy::Fallback(0));
+///
+/// fn update(m: &MainCtx<'_>, speed: u16) {
+///     SPEED.set(m, speed);
+///     SPEED.poison_unless(m, soft_assert!(m, speed <= 1000, ERRORS, 1));
+/// }
+///
+/// fn motor(m: &MainCtx<'_>) {
+///     // Zero while poisoned.
+///     let speed = SPEED.get(m);
+///     // ...
+/// }
+/// ```
+pub struct Poisonable<T> {
+    inner: MainCtxCell<T>,
+    poisoned: MainCtxCell<bool>,
+    policy: PoisonPolicy<T>,
+}
+
+impl<T> Poisonable<T> {
+    /// Create a new, not poisoned state with the initial value `inner`.
+    #[inline(always)]
+    pub const fn new(inner: T, policy: PoisonPolicy<T>) -> Self {
+        Self {
+            inner: MainCtxCell::new(inner),
+            poisoned: MainCtxCell::new(false),
+            policy,
+        }
+    }
+
+    /// Mark the state as poisoned.
+    #[inline(always)]
+    pub fn poison(&self, m: &MainCtx<'_>) {
+        self.poisoned.set(m, true);
+    }
+
+    /// Mark the state as poisoned, if `ok` is `false`.
+    ///
+    /// This can be combined with [crate::soft_assert].
+    /// Returns `ok`.
+    #[inline(always)]
+    pub fn poison_unless(&self, m: &MainCtx<'_>, ok: bool) -> bool {
+        if !ok {
+            self.poison(m);
+        }
+        ok
+    }
+
+    /// Returns `true`, if the state is poisoned.
+    #[inline(always)]
+    pub fn is_poisoned(&self, m: &MainCtx<'_>) -> bool {
+        self.poisoned.get(m)
+    }
+
+    /// Store the known good value `inner` and clear the poison flag.
+    #[inline(always)]
+    pub fn recover(&self, m: &MainCtx<'_>, inner: T) {
+        self.inner.replace(m, inner);
+        self.poisoned.set(m, false);
+    }
+
+    /// Get the configured poison policy.
+    #[inline(always)]
+    pub fn policy(&self) -> &PoisonPolicy<T> {
+        &self.policy
+    }
+}
+
+impl<T: Copy> Poisonable<T> {
+    /// Set a new value.
+    ///
+    /// This does not clear the poison flag. Use [Self::recover] for that.
+    #[inline(always)]
+    pub fn set(&self, m: &MainCtx<'_>, inner: T) {
+        self.inner.set(m, inner);
+    }
+
+    /// Get the value.
+    ///
+    /// If the state is poisoned, then the [PoisonPolicy] applies.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the state is poisoned and the policy is [PoisonPolicy::Panic].
+    #[inline(always)]
+    pub fn get(&self, m: &MainCtx<'_>) -> T {
+        if self.is_poisoned(m) {
+            match self.policy {
+                PoisonPolicy::Panic => panic!("Poisonable: read of poisoned state"),
+                PoisonPolicy::Fallback(safe) => safe,
+            }
+        } else {
+            self.inner.get(m)
+        }
+    }
+
+    /// Get the value, regardless of the configured policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the state is poisoned.
+    #[inline(always)]
+    pub fn get_or_panic_if_poisoned(&self, m: &MainCtx<'_>) -> T {
+        assert!(!self.is_poisoned(m), "Poisonable: read of poisoned state");
+        self.inner.get(m)
+    }
+
+    /// Get the stored value, even if the state is poisoned.
+    ///
+    /// This can be used for diagnostics.
+    #[inline(always)]
+    pub fn get_raw(&self, m: &MainCtx<'_>) -> T {
+        self.inner.get(m)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use crate::{errors::ErrorSink, soft_assert};
+
+    #[test]
+    fn test_poison_fallback() {
+        let m = unsafe { MainCtx::new() };
+
+        let errors = ErrorSink::new();
+        let p = Poisonable::new(10_u16, PoisonPolicy::Fallback(0));
+        assert!(!p.is_poisoned(&m));
+        assert_eq!(p.get(&m), 10);
+
+        let value = 2000;
+        p.set(&m, value);
+        assert!(!p.poison_unless(&m, soft_assert!(&m, value <= 1000, errors, 3)));
+        assert!(p.is_poisoned(&m));
+        assert_eq!(errors.take_errors(&m), 0x0008);
+        assert_eq!(p.get(&m), 0);
+        assert_eq!(p.get_raw(&m), 2000);
+
+        p.set(&m, 20);
+        assert!(p.is_poisoned(&m));
+        assert_eq!(p.get(&m), 0);
+
+        p.recover(&m, 30);
+        assert!(!p.is_poisoned(&m));
+        assert_eq!(p.get(&m), 30);
+        assert_eq!(p.get_or_panic_if_poisoned(&m), 30);
+        assert!(p.poison_unless(&m, true));
+        assert!(!p.is_poisoned(&m));
+    }
+
+    #[test]
+    #[should_panic(expected = "poisoned")]
+    fn test_poison_panic_policy() {
+        let m = unsafe { MainCtx::new() };
+
+        let p = Poisonable::new(1_u8, PoisonPolicy::Panic);
+        assert_eq!(p.get(&m), 1);
+        p.poison(&m);
+        p.get(&m);
+    }
+
+    #[test]
+    #[should_panic(expected = "poisoned")]
+    fn test_poison_get_or_panic() {
+        let m = unsafe { MainCtx::new() };
+
+        let p = Poisonable::new(1_u8, PoisonPolicy::Fallback(0));
+        p.poison(&m);
+        assert_eq!(p.get(&m), 0);
+        p.get_or_panic_if_poisoned(&m);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
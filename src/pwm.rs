@@ -0,0 +1,159 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Software PWM that is configured from `main()` context and stepped in interrupt context.
+
+use crate::{
+    Mutex,
+    cell::MainCtxCellArray,
+    context::{IrqCtx, MainCtx},
+    with_cs,
+};
+use core::cell::Cell;
+
+/// Software PWM with up to 32 channels.
+///
+/// The main loop configures the duty cycles with [Self::set_duty].
+/// A periodic timer interrupt calls [Self::step] and drives the output pins
+/// according to the returned bit mask.
+///
+/// One PWM period has 256 steps.
+/// A channel with the duty cycle `d` is high for `d` steps of each period.
+/// Therefore, a duty cycle of 0 is always low and 255 is high for 255 of 256 steps.
+///
+/// New duty cycles take effect at the start of the next period.
+/// That avoids glitches in the running period.
+pub struct SoftPwm<const N: usize> {
+    /// `main()` context copy of the duty cycles.
+    duty: MainCtxCellArray<u8, N>,
+    /// Duty cycles for the next period.
+    pending: Mutex<[Cell<u8>; N]>,
+    /// Duty cycles of the running period. Only accessed from interrupt context.
+    active: Mutex<[Cell<u8>; N]>,
+    /// Step counter. Only accessed from interrupt context.
+    counter: Mutex<Cell<u8>>,
+}
+
+impl<const N: usize> SoftPwm<N> {
+    /// Create a new software PWM with all channels at duty cycle 0.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        const { assert!(N <= 32) };
+        Self {
+            duty: MainCtxCellArray::new_filled(0),
+            pending: Mutex::new([const { Cell::new(0) }; N]),
+            active: Mutex::new([const { Cell::new(0) }; N]),
+            counter: Mutex::new(Cell::new(0)),
+        }
+    }
+
+    /// Set the `duty` cycle of channel `ch` from `main()` context.
+    ///
+    /// This enters a critical section.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `ch` is out of bounds.
+    #[inline(always)]
+    pub fn set_duty(&self, m: &MainCtx<'_>, ch: usize, duty: u8) {
+        self.duty.set(m, ch, duty);
+        with_cs(|cs| self.pending.borrow(cs)[ch].set(duty));
+    }
+
+    /// Get the duty cycle of channel `ch` from `main()` context.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `ch` is out of bounds.
+    #[inline(always)]
+    pub fn duty(&self, m: &MainCtx<'_>, ch: usize) -> u8 {
+        self.duty.get(m, ch)
+    }
+
+    /// Advance the PWM by one step from interrupt context.
+    ///
+    /// Returns the output levels of all channels.
+    /// Bit `n` is set, if channel `n` shall be high.
+    #[inline(always)]
+    pub fn step(&self, irq: &IrqCtx<'_>) -> u32 {
+        let cs = irq.cs();
+        let counter = self.counter.borrow(cs);
+        let count = counter.get();
+        counter.set(count.wrapping_add(1));
+
+        let active = self.active.borrow(cs);
+        if count == 0 {
+            for (active, pending) in active.iter().zip(self.pending.borrow(cs)) {
+                active.set(pending.get());
+            }
+        }
+        let mut levels = 0;
+        for (ch, duty) in active.iter().enumerate() {
+            if count < duty.get() {
+                levels |= 1 << ch;
+            }
+        }
+        levels
+    }
+}
+
+impl<const N: usize> Default for SoftPwm<N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    fn run_period<const N: usize>(pwm: &SoftPwm<N>) -> [u32; N] {
+        let irq = unsafe { IrqCtx::new() };
+        let mut high = [0; N];
+        for _ in 0..256 {
+            let levels = pwm.step(&irq);
+            for (ch, high) in high.iter_mut().enumerate() {
+                *high += (levels >> ch) & 1;
+            }
+        }
+        high
+    }
+
+    #[test]
+    fn test_soft_pwm() {
+        let m = unsafe { MainCtx::new() };
+
+        let pwm: SoftPwm<3> = SoftPwm::new();
+        assert_eq!(run_period(&pwm), [0, 0, 0]);
+
+        pwm.set_duty(&m, 0, 64);
+        pwm.set_duty(&m, 2, 255);
+        assert_eq!(pwm.duty(&m, 0), 64);
+        assert_eq!(pwm.duty(&m, 1), 0);
+        assert_eq!(run_period(&pwm), [64, 0, 255]);
+
+        // The high phase is at the start of the period.
+        {
+            let irq = unsafe { IrqCtx::new() };
+            for _ in 0..64 {
+                assert_eq!(pwm.step(&irq), 0b101);
+            }
+            assert_eq!(pwm.step(&irq), 0b100);
+        }
+
+        // A change in the running period takes effect in the next period.
+        pwm.set_duty(&m, 1, 10);
+        {
+            let irq = unsafe { IrqCtx::new() };
+            for _ in 65..256 {
+                assert_eq!(pwm.step(&irq) & 0b010, 0);
+            }
+        }
+        assert_eq!(run_period(&pwm), [64, 10, 255]);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,105 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Shadow copies of hardware registers.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+use core::ops::{BitAnd, BitOr, Not};
+
+/// Shadow copy of a hardware register that can only be accessed from `main()` context.
+///
+/// This is useful for registers that are write-only
+/// or that are not suitable for read-modify-write operations.
+///
+/// All modifications update the shadow copy and then write
+/// the new value to the hardware with the user provided `writer` function.
+pub struct RegShadow<T> {
+    shadow: MainCtxCell<T>,
+    writer: fn(T),
+}
+
+impl<T> RegShadow<T> {
+    /// Create a new register shadow with the `initial` register value.
+    ///
+    /// `writer` is called to write a new value to the hardware register.
+    /// The `initial` value is not written to the hardware.
+    #[inline(always)]
+    pub const fn new(initial: T, writer: fn(T)) -> Self {
+        Self {
+            shadow: MainCtxCell::new(initial),
+            writer,
+        }
+    }
+}
+
+impl<T: Copy> RegShadow<T> {
+    /// Get the shadow copy of the register value.
+    #[inline(always)]
+    pub fn read(&self, m: &MainCtx<'_>) -> T {
+        self.shadow.get(m)
+    }
+
+    /// Write `value` to the shadow copy and to the hardware register.
+    #[inline(always)]
+    pub fn write(&self, m: &MainCtx<'_>, value: T) {
+        self.shadow.set(m, value);
+        (self.writer)(value);
+    }
+}
+
+impl<T> RegShadow<T>
+where
+    T: Copy + BitOr<Output = T> + BitAnd<Output = T> + Not<Output = T>,
+{
+    /// Set the bits of `mask` in the shadow copy and write it to the hardware register.
+    #[inline(always)]
+    pub fn set_bits(&self, m: &MainCtx<'_>, mask: T) {
+        self.write(m, self.read(m) | mask);
+    }
+
+    /// Clear the bits of `mask` in the shadow copy and write it to the hardware register.
+    #[inline(always)]
+    pub fn clear_bits(&self, m: &MainCtx<'_>, mask: T) {
+        self.write(m, self.read(m) & !mask);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering::Relaxed};
+
+    static MOCK_REG: AtomicU8 = AtomicU8::new(0);
+    static MOCK_WRITES: AtomicUsize = AtomicUsize::new(0);
+
+    fn write_mock_reg(value: u8) {
+        MOCK_REG.store(value, Relaxed);
+        MOCK_WRITES.fetch_add(1, Relaxed);
+    }
+
+    #[test]
+    fn test_reg_shadow() {
+        let m = unsafe { MainCtx::new() };
+
+        let reg = RegShadow::new(0x01_u8, write_mock_reg);
+        assert_eq!(reg.read(&m), 0x01);
+        assert_eq!(MOCK_WRITES.load(Relaxed), 0);
+
+        reg.set_bits(&m, 0x90);
+        assert_eq!(reg.read(&m), 0x91);
+        assert_eq!(MOCK_REG.load(Relaxed), 0x91);
+
+        reg.clear_bits(&m, 0x11);
+        assert_eq!(reg.read(&m), 0x80);
+        assert_eq!(MOCK_REG.load(Relaxed), 0x80);
+
+        reg.write(&m, 0x42);
+        assert_eq!(reg.read(&m), 0x42);
+        assert_eq!(MOCK_REG.load(Relaxed), 0x42);
+        assert_eq!(MOCK_WRITES.load(Relaxed), 3);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,88 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Fixed size ring buffer.
+//!
+//! This is the single context storage for the queue types of this crate.
+//! It is not `Sync`. The users put it under `Mutex` protection.
+
+use core::{cell::Cell, mem::MaybeUninit};
+
+pub(crate) struct Ring<T, const N: usize> {
+    buf: [Cell<MaybeUninit<T>>; N],
+    head: Cell<usize>,
+    len: Cell<usize>,
+}
+
+impl<T, const N: usize> Ring<T, N> {
+    #[inline(always)]
+    pub(crate) const fn new() -> Self {
+        Self {
+            buf: [const { Cell::new(MaybeUninit::uninit()) }; N],
+            head: Cell::new(0),
+            len: Cell::new(0),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    #[inline(always)]
+    fn slot(&self, offset: usize) -> &Cell<MaybeUninit<T>> {
+        &self.buf[(self.head.get() + offset) % N]
+    }
+
+    /// Append `inner` to the tail.
+    /// Returns `inner` back, if the ring is full.
+    #[inline(always)]
+    pub(crate) fn push(&self, inner: T) -> Result<(), T> {
+        let len = self.len.get();
+        if len >= N {
+            return Err(inner);
+        }
+        self.slot(len).set(MaybeUninit::new(inner));
+        self.len.set(len + 1);
+        Ok(())
+    }
+
+    /// Append `inner` to the tail.
+    /// Removes and returns the element from the head, if the ring is full.
+    #[inline(always)]
+    pub(crate) fn push_overwrite(&self, inner: T) -> Option<T> {
+        let old = if self.len.get() >= N {
+            self.pop()
+        } else {
+            None
+        };
+        match self.push(inner) {
+            Ok(()) => old,
+            // N is zero.
+            Err(inner) => Some(inner),
+        }
+    }
+
+    /// Remove the element from the head.
+    #[inline(always)]
+    pub(crate) fn pop(&self) -> Option<T> {
+        let len = self.len.get();
+        if len == 0 {
+            return None;
+        }
+        let elem = self.slot(0).replace(MaybeUninit::uninit());
+        self.head.set((self.head.get() + 1) % N);
+        self.len.set(len - 1);
+        // SAFETY: All slots from head to head + len are initialized.
+        Some(unsafe { elem.assume_init() })
+    }
+}
+
+impl<T, const N: usize> Drop for Ring<T, N> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
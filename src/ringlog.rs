@@ -0,0 +1,142 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! In-RAM diagnostic logging.
+
+use crate::{Mutex, context::MainCtx, ring::Ring};
+
+/// A fixed size log of the most recent `N` entries
+/// that can only be accessed from `main()` context.
+///
+/// If the log is full, logging a new entry overwrites the oldest entry.
+///
+/// This is useful for post-mortem debugging.
+/// For example, the log can be dumped over UART on a crash.
+pub struct RingLog<T, const N: usize> {
+    ring: Mutex<Ring<T, N>>,
+}
+
+impl<T, const N: usize> RingLog<T, N> {
+    /// Create a new empty log.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            ring: Mutex::new(Ring::new()),
+        }
+    }
+
+    #[inline(always)]
+    fn ring<'a>(&'a self, m: &MainCtx<'a>) -> &'a Ring<T, N> {
+        // SAFETY: We only use the cs for the main context, where it is allowed to be used.
+        self.ring.borrow(unsafe { m.cs() })
+    }
+
+    /// Append `entry` to the log.
+    ///
+    /// The oldest entry is dropped, if the log is full.
+    #[inline(always)]
+    pub fn log(&self, m: &MainCtx<'_>, entry: T) {
+        drop(self.ring(m).push_overwrite(entry));
+    }
+
+    /// Remove all entries from the log and pass them to `f` in insertion order.
+    ///
+    /// Entries that are logged by `f` are not passed to `f` in the same call.
+    #[inline(always)]
+    pub fn drain(&self, m: &MainCtx<'_>, mut f: impl FnMut(T)) {
+        for _ in 0..self.len(m) {
+            if let Some(entry) = self.ring(m).pop() {
+                f(entry);
+            }
+        }
+    }
+
+    /// Get the number of entries in the log.
+    #[inline(always)]
+    pub fn len(&self, m: &MainCtx<'_>) -> usize {
+        self.ring(m).len()
+    }
+
+    /// Returns `true` if the log is empty.
+    #[inline(always)]
+    pub fn is_empty(&self, m: &MainCtx<'_>) -> bool {
+        self.len(m) == 0
+    }
+
+    /// Get the maximum number of entries in the log.
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<T, const N: usize> Default for RingLog<T, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_ring_log() {
+        let m = unsafe { MainCtx::new() };
+
+        let log: RingLog<u8, 3> = RingLog::new();
+        assert!(log.is_empty(&m));
+        log.log(&m, 1);
+        log.log(&m, 2);
+        assert_eq!(log.len(&m), 2);
+
+        let mut out = Vec::new();
+        log.drain(&m, |e| out.push(e));
+        assert_eq!(out, [1, 2]);
+        assert!(log.is_empty(&m));
+    }
+
+    #[test]
+    fn test_ring_log_wraparound() {
+        let m = unsafe { MainCtx::new() };
+
+        let log: RingLog<u16, 3> = RingLog::new();
+        for i in 0..8 {
+            log.log(&m, i);
+        }
+        assert_eq!(log.len(&m), 3);
+
+        let mut out = Vec::new();
+        log.drain(&m, |e| {
+            out.push(e);
+            // Entries logged during the drain stay in the log.
+            log.log(&m, e + 100);
+        });
+        assert_eq!(out, [5, 6, 7]);
+
+        out.clear();
+        log.drain(&m, |e| out.push(e));
+        assert_eq!(out, [105, 106, 107]);
+    }
+
+    #[test]
+    fn test_ring_log_drop() {
+        let m = unsafe { MainCtx::new() };
+
+        let log: RingLog<std::string::String, 2> = RingLog::new();
+        log.log(&m, "a".into());
+        log.log(&m, "b".into());
+        log.log(&m, "c".into());
+        drop(log);
+
+        let empty: RingLog<u8, 0> = RingLog::new();
+        empty.log(&m, 1);
+        assert!(empty.is_empty(&m));
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
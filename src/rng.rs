@@ -0,0 +1,121 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Deterministic pseudo random numbers in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// State that replaces the invalid all-zero seed.
+const ZERO_SEED_REPLACEMENT: u32 = 0x9E37_79B9;
+
+/// Small xorshift pseudo random number generator.
+///
+/// The generator is deterministic. The same seed always produces the same sequence.
+/// This is useful for e.g. jitter and dithering.
+/// It is not suitable for cryptographic purposes.
+pub struct Rng {
+    state: MainCtxCell<u32>,
+}
+
+impl Rng {
+    /// Create a new generator from the given `seed`.
+    ///
+    /// A `seed` of 0 is replaced by a fixed non-zero seed,
+    /// because xorshift would only generate zeros otherwise.
+    #[inline(always)]
+    pub const fn new(seed: u32) -> Self {
+        Self {
+            state: MainCtxCell::new(Self::fix_seed(seed)),
+        }
+    }
+
+    #[inline(always)]
+    const fn fix_seed(seed: u32) -> u32 {
+        if seed == 0 {
+            ZERO_SEED_REPLACEMENT
+        } else {
+            seed
+        }
+    }
+
+    /// Restart the sequence from the given `seed`.
+    ///
+    /// A `seed` of 0 is replaced like in [Self::new].
+    #[inline(always)]
+    pub fn seed(&self, m: &MainCtx<'_>, seed: u32) {
+        self.state.set(m, Self::fix_seed(seed));
+    }
+
+    /// Generate the next pseudo random number.
+    #[inline(always)]
+    pub fn next_u32(&self, m: &MainCtx<'_>) -> u32 {
+        let mut x = self.state.get(m);
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state.set(m, x);
+        x
+    }
+
+    /// Generate the next pseudo random number in the range `0..n`.
+    ///
+    /// The distribution has a slight bias for large `n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `n` is 0.
+    #[inline(always)]
+    pub fn next_range(&self, m: &MainCtx<'_>, n: u32) -> u32 {
+        assert!(n > 0, "Rng::next_range: empty range");
+        ((u64::from(self.next_u32(m)) * u64::from(n)) >> 32) as u32
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let m = unsafe { MainCtx::new() };
+
+        let rng = Rng::new(1);
+        assert_eq!(rng.next_u32(&m), 270369);
+        assert_eq!(rng.next_u32(&m), 67634689);
+        assert_eq!(rng.next_u32(&m), 2647435461);
+
+        // Reproducible from the seed.
+        let mut seq = [0; 8];
+        rng.seed(&m, 12345);
+        for v in &mut seq {
+            *v = rng.next_u32(&m);
+        }
+        let other = Rng::new(12345);
+        for v in seq {
+            assert_eq!(other.next_u32(&m), v);
+        }
+
+        // Zero seed.
+        let zero = Rng::new(0);
+        assert_ne!(zero.next_u32(&m), 0);
+        rng.seed(&m, 0);
+        zero.seed(&m, ZERO_SEED_REPLACEMENT);
+        assert_eq!(rng.next_u32(&m), zero.next_u32(&m));
+
+        for _ in 0..100 {
+            assert!(rng.next_range(&m, 10) < 10);
+            assert_eq!(rng.next_range(&m, 1), 0);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range")]
+    fn test_rng_empty_range() {
+        let m = unsafe { MainCtx::new() };
+        Rng::new(1).next_range(&m, 0);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
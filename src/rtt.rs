@@ -0,0 +1,125 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Debug logging from `main()` context to an RTT channel.
+//!
+//! This module is only available with the `rtt` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use avr_context::{InitCtx, MainCtx, rtt::RttLogger};
+//!
+//! static LOG: RttLogger = RttLogger::new();
+//!
+//! fn init(c: &InitCtx<'_>) {
+//!     # #[cfg(target_arch = "avr")]
+//!     # {
+//!     let channels = rtt_target::rtt_init_default!();
+//!     LOG.set_sink(c.main_ctx(), channels.up.0);
+//!     # }
+//! }
+//!
+//! fn main_loop(m: &MainCtx<'_>) {
+//!     let speed = 42;
+//!     // The result is ignored, if no channel has been set.
+//!     let _ = LOG.write_fmt(m, format_args!("speed = {speed}\n"));
+//! }
+//! ```
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+use core::fmt;
+
+/// A logger that writes formatted text to a `core::fmt::Write` sink
+/// and that can only be used from `main()` context.
+///
+/// Requiring `&MainCtx` prevents the logger from being used in interrupt context.
+/// That is important for sinks like RTT channels, where an interrupt
+/// that interrupts a log write from `main()` could corrupt or deadlock the channel.
+pub struct MainCtxLogger<W> {
+    sink: MainCtxCell<Option<W>>,
+}
+
+/// A [MainCtxLogger] that writes to an RTT up-channel.
+pub type RttLogger = MainCtxLogger<rtt_target::UpChannel>;
+
+impl<W> MainCtxLogger<W> {
+    /// Create a new logger without a sink.
+    ///
+    /// Writes fail until a sink has been set with [Self::set_sink].
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            sink: MainCtxCell::new(None),
+        }
+    }
+
+    /// Set the `sink` for all following writes and return the previous sink.
+    #[inline(always)]
+    pub fn set_sink(&self, m: &MainCtx<'_>, sink: W) -> Option<W> {
+        self.sink.replace(m, Some(sink))
+    }
+
+    /// Remove the sink and return it.
+    #[inline(always)]
+    pub fn take_sink(&self, m: &MainCtx<'_>) -> Option<W> {
+        self.sink.replace(m, None)
+    }
+}
+
+impl<W: fmt::Write> MainCtxLogger<W> {
+    /// Write the formatted `args` to the sink.
+    ///
+    /// Returns an error, if no sink has been set or if the sink failed.
+    ///
+    /// The sink is moved out of the logger while it is written.
+    /// Writes from within the formatting of `args` fail.
+    #[inline(always)]
+    pub fn write_fmt(&self, m: &MainCtx<'_>, args: fmt::Arguments<'_>) -> fmt::Result {
+        let Some(mut sink) = self.take_sink(m) else {
+            return Err(fmt::Error);
+        };
+        let res = sink.write_fmt(args);
+        self.set_sink(m, sink);
+        res
+    }
+
+    /// Write the string `s` to the sink.
+    ///
+    /// See [Self::write_fmt].
+    #[inline(always)]
+    pub fn write_str(&self, m: &MainCtx<'_>, s: &str) -> fmt::Result {
+        self.write_fmt(m, format_args!("{s}"))
+    }
+}
+
+impl<W> Default for MainCtxLogger<W> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use std::string::String;
+
+    #[test]
+    fn test_main_ctx_logger() {
+        let m = unsafe { MainCtx::new() };
+
+        let log: MainCtxLogger<String> = MainCtxLogger::new();
+        assert!(log.write_str(&m, "lost").is_err());
+
+        assert!(log.set_sink(&m, String::new()).is_none());
+        log.write_fmt(&m, format_args!("a={} ", 1)).unwrap();
+        log.write_str(&m, "b").unwrap();
+        assert_eq!(log.take_sink(&m).unwrap(), "a=1 b");
+        assert!(log.take_sink(&m).is_none());
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
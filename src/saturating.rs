@@ -0,0 +1,147 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Saturating accumulators in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// Integer or fixed-point accumulator that is clamped to bounds
+/// and that can only be accessed from `main()` context.
+///
+/// This is useful for the integrator of a PID controller,
+/// which must not wind up beyond the range of the actuator.
+pub struct Saturating<T> {
+    value: MainCtxCell<T>,
+    min: T,
+    max: T,
+}
+
+impl<T: Copy> Saturating<T> {
+    /// Get the current value.
+    #[inline(always)]
+    pub fn get(&self, m: &MainCtx<'_>) -> T {
+        self.value.get(m)
+    }
+
+    /// Get the lower bound.
+    #[inline(always)]
+    pub fn min(&self) -> T {
+        self.min
+    }
+
+    /// Get the upper bound.
+    #[inline(always)]
+    pub fn max(&self) -> T {
+        self.max
+    }
+}
+
+macro_rules! impl_saturating_int {
+    ($($ty:ty),*) => {
+        $(
+            impl Saturating<$ty> {
+                /// Create a new accumulator with the bounds `min` and `max`.
+                ///
+                /// The `initial` value is clamped to the bounds.
+                ///
+                /// # Panics
+                ///
+                /// Panics, if `min` is bigger than `max`.
+                #[inline(always)]
+                pub const fn new(initial: $ty, min: $ty, max: $ty) -> Self {
+                    assert!(min <= max, "Saturating: min > max");
+                    Self {
+                        value: MainCtxCell::new(Self::clamp_to(initial, min, max)),
+                        min,
+                        max,
+                    }
+                }
+
+                #[inline(always)]
+                const fn clamp_to(v: $ty, min: $ty, max: $ty) -> $ty {
+                    if v < min {
+                        min
+                    } else if v > max {
+                        max
+                    } else {
+                        v
+                    }
+                }
+
+                /// Set the value. It is clamped to the bounds.
+                #[inline(always)]
+                pub fn set(&self, m: &MainCtx<'_>, v: $ty) {
+                    self.value.set(m, Self::clamp_to(v, self.min, self.max));
+                }
+
+                /// Add `v` to the value and clamp the result to the bounds.
+                ///
+                /// Returns the new value.
+                #[inline(always)]
+                pub fn add_saturating(&self, m: &MainCtx<'_>, v: $ty) -> $ty {
+                    let new = Self::clamp_to(self.get(m).saturating_add(v), self.min, self.max);
+                    self.value.set(m, new);
+                    new
+                }
+
+                /// Returns `true`, if the value is at one of the bounds.
+                #[inline(always)]
+                pub fn is_saturated(&self, m: &MainCtx<'_>) -> bool {
+                    let v = self.get(m);
+                    v == self.min || v == self.max
+                }
+            }
+        )*
+    };
+}
+
+impl_saturating_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_saturating() {
+        let m = unsafe { MainCtx::new() };
+
+        let integ = Saturating::<i16>::new(0, -100, 100);
+        assert_eq!(integ.add_saturating(&m, 60), 60);
+        assert!(!integ.is_saturated(&m));
+        assert_eq!(integ.add_saturating(&m, 60), 100);
+        assert!(integ.is_saturated(&m));
+        // No wind up: one step back leaves the upper bound immediately.
+        assert_eq!(integ.add_saturating(&m, -1), 99);
+
+        assert_eq!(integ.add_saturating(&m, -150), -51);
+        assert_eq!(integ.add_saturating(&m, i16::MIN), -100);
+        assert_eq!(integ.get(&m), -100);
+        assert!(integ.is_saturated(&m));
+
+        integ.set(&m, 1000);
+        assert_eq!(integ.get(&m), 100);
+        assert_eq!((integ.min(), integ.max()), (-100, 100));
+    }
+
+    #[test]
+    fn test_saturating_type_bounds() {
+        let m = unsafe { MainCtx::new() };
+
+        let acc = Saturating::<u8>::new(7, 0, u8::MAX);
+        assert_eq!(acc.add_saturating(&m, 250), u8::MAX);
+        assert_eq!(acc.add_saturating(&m, 1), u8::MAX);
+
+        let acc = Saturating::<u16>::new(500, 10, 20);
+        assert_eq!(acc.get(&m), 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "min > max")]
+    fn test_saturating_invalid_bounds() {
+        let _ = Saturating::<i8>::new(0, 1, -1);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
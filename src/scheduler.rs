@@ -0,0 +1,166 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Cooperative task scheduling in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx, flag::IrqFlag, with_cs};
+
+/// A task function that runs in `main()` context.
+pub type Task = fn(&MainCtx<'_>);
+
+/// Cooperative priority scheduler for `N` tasks in `main()` context.
+///
+/// Each task has an id in the range `0..N`, a priority and a ready flag.
+/// The ready flag can be set from interrupt context via [Self::flag]
+/// or from main context via [Self::set_ready].
+///
+/// The main loop calls [Self::run_highest_ready] to run the
+/// ready task with the highest priority.
+/// If multiple ready tasks have the same priority, the task with the lowest id runs first.
+pub struct PriorityScheduler<const N: usize> {
+    tasks: [MainCtxCell<Option<(u8, Task)>>; N],
+    ready: [IrqFlag; N],
+}
+
+impl<const N: usize> PriorityScheduler<N> {
+    /// Create a new scheduler without tasks.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            tasks: [const { MainCtxCell::new(None) }; N],
+            ready: [const { IrqFlag::new() }; N],
+        }
+    }
+
+    /// Register the `task` with the given `priority` as task `id`.
+    ///
+    /// A higher `priority` value means a more urgent task.
+    /// A previously registered task with the same `id` is replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is not smaller than `N`.
+    #[inline(always)]
+    pub fn register(&self, m: &MainCtx<'_>, id: usize, priority: u8, task: Task) {
+        self.tasks[id].set(m, Some((priority, task)));
+    }
+
+    /// Get the ready flag of task `id`.
+    ///
+    /// Setting the flag from interrupt context makes the task ready.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is not smaller than `N`.
+    #[inline(always)]
+    pub fn flag(&self, id: usize) -> &IrqFlag {
+        &self.ready[id]
+    }
+
+    /// Make task `id` ready from `main()` context.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is not smaller than `N`.
+    #[inline(always)]
+    pub fn set_ready(&self, _: &MainCtx<'_>, id: usize) {
+        with_cs(|cs| self.ready[id].set_with_cs(cs));
+    }
+
+    /// Run the ready task with the highest priority and clear its ready flag.
+    ///
+    /// The task runs outside of the critical section.
+    ///
+    /// Returns `true`, if a task has been run.
+    pub fn run_highest_ready(&self, m: &MainCtx<'_>) -> bool {
+        let task = with_cs(|cs| {
+            let mut best: Option<(usize, u8, Task)> = None;
+            for (id, task) in self.tasks.iter().enumerate() {
+                if let Some((priority, task)) = task.get(m)
+                    && self.ready[id].is_set(cs)
+                    && best.is_none_or(|(_, best_prio, _)| priority > best_prio)
+                {
+                    best = Some((id, priority, task));
+                }
+            }
+            best.map(|(id, _, task)| {
+                self.ready[id].take_with_cs(cs);
+                task
+            })
+        });
+        if let Some(task) = task {
+            task(m);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<const N: usize> Default for PriorityScheduler<N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use crate::IrqCtx;
+
+    static RUN_LOG: MainCtxCell<[u8; 4]> = MainCtxCell::new([0; 4]);
+    static RUN_COUNT: MainCtxCell<usize> = MainCtxCell::new(0);
+
+    fn log(m: &MainCtx<'_>, id: u8) {
+        let count = RUN_COUNT.get(m);
+        let mut log = RUN_LOG.get(m);
+        log[count] = id;
+        RUN_LOG.set(m, log);
+        RUN_COUNT.set(m, count + 1);
+    }
+
+    fn task_a(m: &MainCtx<'_>) {
+        log(m, b'a');
+    }
+
+    fn task_b(m: &MainCtx<'_>) {
+        log(m, b'b');
+    }
+
+    fn task_c(m: &MainCtx<'_>) {
+        log(m, b'c');
+    }
+
+    #[test]
+    fn test_priority_scheduler() {
+        let m = unsafe { MainCtx::new() };
+
+        let sched: PriorityScheduler<4> = PriorityScheduler::new();
+        sched.register(&m, 0, 1, task_a);
+        sched.register(&m, 1, 5, task_b);
+        sched.register(&m, 2, 3, task_c);
+        assert!(!sched.run_highest_ready(&m));
+
+        // Unregistered tasks never run.
+        sched.set_ready(&m, 3);
+        assert!(!sched.run_highest_ready(&m));
+
+        sched.set_ready(&m, 0);
+        sched.set_ready(&m, 2);
+        {
+            let irq = unsafe { IrqCtx::new() };
+            sched.flag(1).set(&irq);
+        }
+        assert!(sched.run_highest_ready(&m));
+        assert!(sched.run_highest_ready(&m));
+        assert!(sched.run_highest_ready(&m));
+        assert!(!sched.run_highest_ready(&m));
+        assert_eq!(RUN_COUNT.get(&m), 3);
+        assert_eq!(&RUN_LOG.get(&m)[..3], b"bca");
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,177 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Scatter-gather descriptor chains for DMA style interrupt handlers.
+
+use crate::{
+    cell::IrqCtxCell,
+    context::{InitCtx, IrqCtx},
+};
+use core::cell::UnsafeCell;
+
+/// One memory block of a scatter-gather transfer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Descriptor {
+    /// Start address of the memory block.
+    pub addr: usize,
+    /// Length of the memory block in bytes.
+    pub len: u16,
+}
+
+/// Chain of up to `N` scatter-gather [Descriptor]s.
+///
+/// The chain is set up in the initialization phase with [Self::setup].
+/// The transfer complete interrupt then calls [Self::advance]
+/// to get the next block that shall be transferred.
+pub struct SgChain<const N: usize> {
+    /// The descriptors and the number of used descriptors.
+    /// Only written from `InitCtx`.
+    chain: UnsafeCell<([Descriptor; N], usize)>,
+    /// Index of the next descriptor.
+    pos: IrqCtxCell<usize>,
+}
+
+impl<const N: usize> SgChain<N> {
+    /// Create a new empty chain.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            chain: UnsafeCell::new(([Descriptor { addr: 0, len: 0 }; N], 0)),
+            pos: IrqCtxCell::new(0),
+        }
+    }
+
+    /// Set up the chain with the `descriptors` in the initialization phase.
+    ///
+    /// The next call to [Self::advance] returns the first descriptor.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if there are more than `N` descriptors.
+    #[inline(always)]
+    pub fn setup(&self, c: &InitCtx<'_>, descriptors: &[Descriptor]) {
+        assert!(descriptors.len() <= N, "SgChain: too many descriptors");
+        // SAFETY:
+        // The `InitCtx` ensures that there are no concurrent accesses
+        // and no references from `advance` are alive.
+        let chain = unsafe { &mut *self.chain.get() };
+        chain.0[..descriptors.len()].copy_from_slice(descriptors);
+        chain.1 = descriptors.len();
+        self.pos.init(c, 0);
+    }
+
+    #[inline(always)]
+    fn descriptors(&self) -> &[Descriptor] {
+        // SAFETY: The chain is only written in `setup` from `InitCtx`.
+        let chain = unsafe { &*self.chain.get() };
+        &chain.0[..chain.1]
+    }
+
+    /// Get the next descriptor from interrupt context.
+    ///
+    /// Returns `None`, if the whole chain has been processed.
+    #[inline(always)]
+    pub fn advance<'a>(&'a self, irq: &'a IrqCtx<'_>) -> Option<&'a Descriptor> {
+        let pos = self.pos.get(irq);
+        let desc = self.descriptors().get(pos)?;
+        self.pos.set(irq, pos + 1);
+        Some(desc)
+    }
+
+    /// Get the number of descriptors that have not been returned by [Self::advance].
+    #[inline(always)]
+    pub fn remaining(&self, irq: &IrqCtx<'_>) -> usize {
+        self.descriptors().len() - self.pos.get(irq)
+    }
+
+    /// Returns `true`, if the whole chain has been processed.
+    #[inline(always)]
+    pub fn is_done(&self, irq: &IrqCtx<'_>) -> bool {
+        self.remaining(irq) == 0
+    }
+
+    /// Restart the chain from the first descriptor from interrupt context.
+    #[inline(always)]
+    pub fn restart(&self, irq: &IrqCtx<'_>) {
+        self.pos.set(irq, 0);
+    }
+}
+
+impl<const N: usize> Default for SgChain<N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY:
+// The descriptors are only written from `InitCtx`, where no other code runs.
+// Afterwards they are only read.
+unsafe impl<const N: usize> Sync for SgChain<N> {}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use crate::context::MainCtx;
+
+    static CHAIN: SgChain<4> = SgChain::new();
+
+    #[test]
+    fn test_sg_chain() {
+        fn init(c: &InitCtx<'_>, _: ()) {
+            CHAIN.setup(
+                c,
+                &[
+                    Descriptor {
+                        addr: 0x100,
+                        len: 64,
+                    },
+                    Descriptor {
+                        addr: 0x200,
+                        len: 16,
+                    },
+                    Descriptor {
+                        addr: 0x180,
+                        len: 8,
+                    },
+                ],
+            );
+        }
+        let (m, ()) = unsafe { MainCtx::new_with_init(init, ()) };
+
+        let irq = unsafe { IrqCtx::new() };
+        assert_eq!(CHAIN.remaining(&irq), 3);
+        assert_eq!(CHAIN.advance(&irq).unwrap().addr, 0x100);
+        let desc = CHAIN.advance(&irq).unwrap();
+        assert_eq!(
+            *desc,
+            Descriptor {
+                addr: 0x200,
+                len: 16
+            }
+        );
+        assert!(!CHAIN.is_done(&irq));
+        assert_eq!(CHAIN.advance(&irq).unwrap().len, 8);
+        assert!(CHAIN.is_done(&irq));
+        assert_eq!(CHAIN.advance(&irq), None);
+        assert_eq!(CHAIN.advance(&irq), None);
+
+        CHAIN.restart(&irq);
+        assert_eq!(CHAIN.remaining(&irq), 3);
+        assert_eq!(CHAIN.advance(&irq).unwrap().addr, 0x100);
+        drop(irq);
+        drop(m);
+    }
+
+    #[test]
+    fn test_sg_chain_empty() {
+        let chain: SgChain<2> = SgChain::new();
+        let irq = unsafe { IrqCtx::new() };
+        assert!(chain.is_done(&irq));
+        assert_eq!(chain.advance(&irq), None);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,55 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Simulation of interrupts on the host for integration tests.
+//!
+//! This module is only available with the `std-test` feature on non-AVR targets.
+//!
+//! A pseudo interrupt service routine runs in a separate thread.
+//! Each invocation of the pseudo ISR holds the global critical section,
+//! like a real ISR runs with interrupts disabled.
+//! Therefore, the pseudo ISR is serialized against all critical sections
+//! that are entered from the `main()` thread.
+//!
+//! Only one thread may use the `MainCtx`.
+//! All `MainCtxCell`s must only be accessed from that thread.
+
+use crate::context::IrqCtx;
+use std::{
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Run `f` as a pseudo interrupt service routine on the current thread.
+///
+/// `f` runs within the global critical section.
+#[inline]
+pub fn run_isr<R>(f: impl FnOnce(&IrqCtx<'_>) -> R) -> R {
+    critical_section::with(|_| {
+        // SAFETY: We are holding the global critical section,
+        // which serializes us against all other pseudo ISRs and critical sections.
+        let irq = unsafe { IrqCtx::new() };
+        f(&irq)
+    })
+}
+
+/// Spawn a thread that fires the pseudo interrupt service routine `f`
+/// `count` times every `period`.
+///
+/// `f` is called with the invocation number, starting at 0.
+/// Each invocation runs with [run_isr].
+pub fn spawn_isr(
+    period: Duration,
+    count: usize,
+    mut f: impl FnMut(&IrqCtx<'_>, usize) + Send + 'static,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        for i in 0..count {
+            run_isr(|irq| f(irq, i));
+            thread::sleep(period);
+        }
+    })
+}
+
+// vim: ts=4 sw=4 expandtab
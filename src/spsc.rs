@@ -0,0 +1,181 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Lock-free queue from interrupt context to `main()` context.
+
+use crate::context::{IrqCtx, MainCtx};
+use core::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+/// Single-producer single-consumer queue
+/// from interrupt context to `main()` context.
+///
+/// Elements can only be enqueued from interrupt context
+/// and only be dequeued from `main()` context.
+///
+/// The queue does not need critical sections.
+/// It only uses byte sized atomic loads and stores,
+/// which compile to plain memory accesses on AVR.
+///
+/// The queue can hold up to `N - 1` elements.
+/// `N` must be in the range `1..=256`.
+pub struct SpscQueue<T, const N: usize> {
+    buf: [UnsafeCell<MaybeUninit<T>>; N],
+    /// Next slot to dequeue. Only written by the consumer.
+    head: AtomicU8,
+    /// Next slot to enqueue. Only written by the producer.
+    tail: AtomicU8,
+}
+
+impl<T, const N: usize> SpscQueue<T, N> {
+    /// Create a new empty queue.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        const { assert!(N >= 1 && N <= 256) };
+        Self {
+            buf: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+            head: AtomicU8::new(0),
+            tail: AtomicU8::new(0),
+        }
+    }
+
+    #[inline(always)]
+    fn next(index: u8) -> u8 {
+        ((index as usize + 1) % N) as u8
+    }
+
+    /// Enqueue an element from interrupt context.
+    ///
+    /// Returns `inner` back, if the queue is full.
+    #[inline(always)]
+    pub fn enqueue(&self, _: &IrqCtx<'_>, inner: T) -> Result<(), T> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let next = Self::next(tail);
+        if next == self.head.load(Ordering::Acquire) {
+            return Err(inner);
+        }
+        // SAFETY:
+        // The slot at `tail` is not visible to the consumer until `tail` is advanced.
+        // Producers are serialized, because they run in interrupt context
+        // with interrupts disabled.
+        unsafe { (*self.buf[tail as usize].get()).write(inner) };
+        self.tail.store(next, Ordering::Release);
+        Ok(())
+    }
+
+    /// Dequeue an element from `main()` context.
+    #[inline(always)]
+    pub fn dequeue(&self, _: &MainCtx<'_>) -> Option<T> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY:
+        // The slot at `head` has been initialized by the producer
+        // and it is not touched by the producer until `head` is advanced.
+        // There is only one consumer, because there is only one main context.
+        let inner = unsafe { (*self.buf[head as usize].get()).assume_init_read() };
+        self.head.store(Self::next(head), Ordering::Release);
+        Some(inner)
+    }
+
+    /// Get the number of elements in the queue from `main()` context.
+    #[inline(always)]
+    pub fn len(&self, _: &MainCtx<'_>) -> usize {
+        let head = self.head.load(Ordering::Relaxed) as usize;
+        let tail = self.tail.load(Ordering::Acquire) as usize;
+        (tail + N - head) % N
+    }
+
+    /// Returns `true` if the queue is empty.
+    #[inline(always)]
+    pub fn is_empty(&self, m: &MainCtx<'_>) -> bool {
+        self.len(m) == 0
+    }
+
+    /// Get the maximum number of elements in the queue.
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        N - 1
+    }
+}
+
+impl<T, const N: usize> Default for SpscQueue<T, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for SpscQueue<T, N> {
+    fn drop(&mut self) {
+        let mut head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        while head != tail {
+            // SAFETY: All slots from head to tail are initialized.
+            unsafe { self.buf[head as usize].get_mut().assume_init_drop() };
+            head = Self::next(head);
+        }
+    }
+}
+
+// SAFETY: If T is Send, then we can Send the whole object.
+unsafe impl<T: Send, const N: usize> Send for SpscQueue<T, N> {}
+
+// SAFETY:
+// Enqueueing is only possible from interrupt context and dequeueing is
+// only possible from main context. The slots are handed over between
+// producer and consumer with acquire/release ordering.
+unsafe impl<T: Send, const N: usize> Sync for SpscQueue<T, N> {}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_spsc_queue() {
+        let m = unsafe { MainCtx::new() };
+
+        let q: SpscQueue<u16, 4> = SpscQueue::new();
+        assert_eq!(q.capacity(), 3);
+        assert!(q.is_empty(&m));
+        assert_eq!(q.dequeue(&m), None);
+
+        for round in 0..3 {
+            {
+                let irq = unsafe { IrqCtx::new() };
+                assert_eq!(q.enqueue(&irq, round), Ok(()));
+                assert_eq!(q.enqueue(&irq, round + 10), Ok(()));
+                assert_eq!(q.enqueue(&irq, round + 20), Ok(()));
+                assert_eq!(q.enqueue(&irq, round + 30), Err(round + 30));
+            }
+            assert_eq!(q.len(&m), 3);
+            assert_eq!(q.dequeue(&m), Some(round));
+            assert_eq!(q.dequeue(&m), Some(round + 10));
+            assert_eq!(q.dequeue(&m), Some(round + 20));
+            assert_eq!(q.dequeue(&m), None);
+        }
+    }
+
+    #[test]
+    fn test_spsc_queue_drop() {
+        use std::rc::Rc;
+
+        let irq = unsafe { IrqCtx::new() };
+
+        let rc = Rc::new(());
+        let q: SpscQueue<Rc<()>, 3> = SpscQueue::new();
+        q.enqueue(&irq, Rc::clone(&rc)).unwrap();
+        q.enqueue(&irq, Rc::clone(&rc)).unwrap();
+        assert_eq!(Rc::strong_count(&rc), 3);
+        drop(q);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,192 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Stack canary for detecting stack overflows in debug builds.
+
+/// The value that is painted into the canary bytes.
+const CANARY: u8 = 0xA5;
+
+/// The number of canary bytes.
+const CANARY_LEN: usize = 8;
+
+/// Access to the stack pointer and to the stack memory.
+pub(crate) trait StackMem {
+    /// Get the current stack pointer.
+    fn sp(&self) -> usize;
+
+    /// Get the lowest address that the stack may grow down to.
+    ///
+    /// This is the end of the statically allocated data.
+    fn limit(&self) -> usize;
+
+    /// Read the byte at `addr`.
+    ///
+    /// # Safety
+    ///
+    /// `addr` must be a valid stack address.
+    unsafe fn read(&self, addr: usize) -> u8;
+
+    /// Write the byte `value` to `addr`.
+    ///
+    /// # Safety
+    ///
+    /// `addr` must be a valid and currently unused stack address.
+    unsafe fn write(&self, addr: usize, value: u8);
+}
+
+/// The AVR hardware stack.
+#[cfg(target_arch = "avr")]
+pub(crate) struct AvrStack;
+
+#[cfg(target_arch = "avr")]
+impl StackMem for AvrStack {
+    #[inline(never)]
+    fn sp(&self) -> usize {
+        // The address of a local variable approximates the stack pointer.
+        // The frame of this function is below the frame of the caller.
+        // That avoids the SPL/SPH data space addresses,
+        // which differ between the classic, XMEGA and AVRxt cores.
+        let marker = 0_u8;
+        core::hint::black_box(&marker) as *const u8 as usize
+    }
+
+    #[inline(always)]
+    fn limit(&self) -> usize {
+        unsafe extern "C" {
+            /// End of `.data`, `.bss` and `.noinit` from the avr-libc linker script.
+            static __heap_start: u8;
+        }
+        &raw const __heap_start as usize
+    }
+
+    #[inline(always)]
+    unsafe fn read(&self, addr: usize) -> u8 {
+        // SAFETY: See the safety contract of this function.
+        unsafe { (addr as *const u8).read_volatile() }
+    }
+
+    #[inline(always)]
+    unsafe fn write(&self, addr: usize, value: u8) {
+        // SAFETY: See the safety contract of this function.
+        unsafe { (addr as *mut u8).write_volatile(value) }
+    }
+}
+
+/// Paint the canary `depth` bytes below the current stack pointer,
+/// run `f` and check the canary afterwards.
+///
+/// # Panics
+///
+/// Panics, if the canary would be below the stack limit of `mem`.
+///
+/// Panics, if `f` used more than `depth` bytes of stack
+/// and overwrote the canary.
+pub(crate) fn guard<R>(mem: &impl StackMem, depth: usize, f: impl FnOnce() -> R) -> R {
+    let bottom = mem
+        .sp()
+        .checked_sub(depth.saturating_add(CANARY_LEN))
+        .filter(|&bottom| bottom >= mem.limit())
+        .expect("stack guard: depth is larger than the free stack");
+    for addr in bottom..bottom + CANARY_LEN {
+        // SAFETY: The canary is below the stack pointer and above the stack limit
+        // in unused stack memory.
+        unsafe { mem.write(addr, CANARY) };
+    }
+    let ret = f();
+    for addr in bottom..bottom + CANARY_LEN {
+        // SAFETY: The canary is within the stack.
+        if unsafe { mem.read(addr) } != CANARY {
+            panic!("stack guard: stack usage exceeded {depth} bytes");
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use core::cell::Cell;
+
+    /// A mocked stack that grows downwards from the top of `mem`
+    /// with static data in the lowest 8 bytes.
+    struct MockStack {
+        mem: [Cell<u8>; 64],
+        sp: Cell<usize>,
+    }
+
+    impl MockStack {
+        fn new() -> Self {
+            Self {
+                mem: [const { Cell::new(0) }; 64],
+                sp: Cell::new(63),
+            }
+        }
+
+        /// Simulate a function that uses `bytes` of stack.
+        fn use_stack(&self, bytes: usize) {
+            let sp = self.sp.get();
+            for addr in sp - bytes..sp {
+                self.mem[addr].set(0x11);
+            }
+        }
+    }
+
+    impl StackMem for MockStack {
+        fn sp(&self) -> usize {
+            self.sp.get()
+        }
+
+        fn limit(&self) -> usize {
+            8
+        }
+
+        unsafe fn read(&self, addr: usize) -> u8 {
+            self.mem[addr].get()
+        }
+
+        unsafe fn write(&self, addr: usize, value: u8) {
+            self.mem[addr].set(value);
+        }
+    }
+
+    #[test]
+    fn test_stack_guard_intact() {
+        let stack = MockStack::new();
+        assert_eq!(guard(&stack, 16, || 42), 42);
+        guard(&stack, 16, || stack.use_stack(16));
+        assert_eq!(stack.mem[63 - 16 - 1].get(), CANARY);
+        assert_eq!(stack.mem[63 - 16 - CANARY_LEN].get(), CANARY);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeded 16 bytes")]
+    fn test_stack_guard_broken() {
+        let stack = MockStack::new();
+        guard(&stack, 16, || stack.use_stack(17));
+    }
+
+    #[test]
+    fn test_stack_guard_limit() {
+        let stack = MockStack::new();
+        guard(&stack, 63 - 8 - CANARY_LEN, || ());
+        assert_eq!(stack.mem[8].get(), CANARY);
+    }
+
+    #[test]
+    #[should_panic(expected = "larger than the free stack")]
+    fn test_stack_guard_too_deep() {
+        let stack = MockStack::new();
+        guard(&stack, 63 - 8 - CANARY_LEN + 1, || ());
+    }
+
+    #[test]
+    #[should_panic(expected = "larger than the free stack")]
+    fn test_stack_guard_overflow() {
+        let stack = MockStack::new();
+        guard(&stack, usize::MAX, || ());
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,182 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Simple state machines in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// A state transition hook.
+///
+/// The hook is called with the state that is entered or exited.
+pub type Hook<S> = fn(&MainCtx<'_>, S);
+
+/// A state machine that can only be accessed from `main()` context.
+///
+/// The optional [Self::on_exit] hook is called with the old state
+/// and the optional [Self::on_enter] hook is called with the new state
+/// on every [Self::transition].
+///
+/// With the `state-log` feature the state machine also records
+/// the number of transitions and the most recent transition.
+pub struct StateMachine<S> {
+    state: MainCtxCell<S>,
+    on_enter: Option<Hook<S>>,
+    on_exit: Option<Hook<S>>,
+    #[cfg(feature = "state-log")]
+    log: MainCtxCell<(u16, Option<(S, S)>)>,
+}
+
+impl<S> StateMachine<S> {
+    /// Create a new state machine in the `initial` state without hooks.
+    #[inline(always)]
+    pub const fn new(initial: S) -> Self {
+        Self {
+            state: MainCtxCell::new(initial),
+            on_enter: None,
+            on_exit: None,
+            #[cfg(feature = "state-log")]
+            log: MainCtxCell::new((0, None)),
+        }
+    }
+
+    /// Set the hook that is called after a state has been entered.
+    #[inline(always)]
+    pub const fn on_enter(mut self, hook: Hook<S>) -> Self {
+        self.on_enter = Some(hook);
+        self
+    }
+
+    /// Set the hook that is called before a state is exited.
+    #[inline(always)]
+    pub const fn on_exit(mut self, hook: Hook<S>) -> Self {
+        self.on_exit = Some(hook);
+        self
+    }
+}
+
+impl<S: Copy> StateMachine<S> {
+    /// Get the current state.
+    #[inline(always)]
+    pub fn state(&self, m: &MainCtx<'_>) -> S {
+        self.state.get(m)
+    }
+
+    /// Transition from the current state to the `next` state.
+    ///
+    /// The `on_exit` hook is called with the current state before the transition.
+    /// The `on_enter` hook is called with the `next` state after the transition.
+    /// The hooks are also called, if `next` is equal to the current state.
+    #[inline(always)]
+    pub fn transition(&self, m: &MainCtx<'_>, next: S) {
+        let prev = self.state.get(m);
+        if let Some(on_exit) = self.on_exit {
+            on_exit(m, prev);
+        }
+        self.state.set(m, next);
+        #[cfg(feature = "state-log")]
+        {
+            let (count, _) = self.log.get(m);
+            self.log.set(m, (count.wrapping_add(1), Some((prev, next))));
+        }
+        if let Some(on_enter) = self.on_enter {
+            on_enter(m, next);
+        }
+    }
+
+    /// Get the number of transitions.
+    ///
+    /// The counter wraps around on overflow.
+    #[cfg(feature = "state-log")]
+    #[inline(always)]
+    pub fn transitions(&self, m: &MainCtx<'_>) -> u16 {
+        self.log.get(m).0
+    }
+
+    /// Get the most recent transition as `(from, to)`.
+    ///
+    /// Returns `None`, if no transition has happened, yet.
+    #[cfg(feature = "state-log")]
+    #[inline(always)]
+    pub fn last_transition(&self, m: &MainCtx<'_>) -> Option<(S, S)> {
+        self.log.get(m).1
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Led {
+        Off,
+        On,
+        Blink,
+    }
+
+    static HOOK_LOG: MainCtxCell<[Option<(bool, Led)>; 4]> = MainCtxCell::new([None; 4]);
+
+    fn log_hook(m: &MainCtx<'_>, enter: bool, state: Led) {
+        let mut log = HOOK_LOG.get(m);
+        log.rotate_left(1);
+        log[3] = Some((enter, state));
+        HOOK_LOG.set(m, log);
+    }
+
+    fn on_enter(m: &MainCtx<'_>, state: Led) {
+        log_hook(m, true, state);
+    }
+
+    fn on_exit(m: &MainCtx<'_>, state: Led) {
+        log_hook(m, false, state);
+    }
+
+    #[test]
+    fn test_state_machine() {
+        let m = unsafe { MainCtx::new() };
+
+        let sm = StateMachine::new(Led::Off)
+            .on_enter(on_enter)
+            .on_exit(on_exit);
+        assert_eq!(sm.state(&m), Led::Off);
+
+        sm.transition(&m, Led::On);
+        assert_eq!(sm.state(&m), Led::On);
+        sm.transition(&m, Led::Blink);
+        assert_eq!(sm.state(&m), Led::Blink);
+        assert_eq!(
+            HOOK_LOG.get(&m),
+            [
+                Some((false, Led::Off)),
+                Some((true, Led::On)),
+                Some((false, Led::On)),
+                Some((true, Led::Blink)),
+            ]
+        );
+
+        #[cfg(feature = "state-log")]
+        {
+            assert_eq!(sm.transitions(&m), 2);
+            assert_eq!(sm.last_transition(&m), Some((Led::On, Led::Blink)));
+        }
+    }
+
+    #[test]
+    fn test_state_machine_no_hooks() {
+        let m = unsafe { MainCtx::new() };
+
+        let sm = StateMachine::new(1_u8);
+        sm.transition(&m, 2);
+        sm.transition(&m, 2);
+        assert_eq!(sm.state(&m), 2);
+
+        #[cfg(feature = "state-log")]
+        {
+            assert_eq!(sm.transitions(&m), 2);
+            assert_eq!(sm.last_transition(&m), Some((2, 2)));
+        }
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
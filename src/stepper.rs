@@ -0,0 +1,204 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Stepper motor step generator that is configured from `main()` context
+//! and stepped in interrupt context.
+
+use crate::{
+    Mutex,
+    cell::IrqCtxCell,
+    context::{IrqCtx, MainCtx},
+    with_cs,
+};
+use core::cell::Cell;
+
+/// Action to be performed by the interrupt service routine after [StepGen::step].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepAction {
+    /// The target position has been reached. Do nothing.
+    Idle,
+    /// Waiting for the next step. Do nothing.
+    Wait,
+    /// Emit one step pulse in forward direction.
+    Forward,
+    /// Emit one step pulse in backward direction.
+    Backward,
+}
+
+/// Step generator for stepper motors and servos.
+///
+/// The main loop configures the target position with [Self::set_target]
+/// and the step rate with [Self::set_interval].
+/// A periodic timer interrupt calls [Self::step] and emits
+/// step pulses according to the returned [StepAction].
+///
+/// The position is counted in steps.
+pub struct StepGen {
+    /// Current position. Updated from interrupt context.
+    position: Mutex<Cell<i32>>,
+    /// Target position. Set from `main()` context.
+    target: Mutex<Cell<i32>>,
+    /// Number of [Self::step] calls per step pulse.
+    interval: Mutex<Cell<u16>>,
+    /// Remaining [Self::step] calls until the next step pulse.
+    countdown: IrqCtxCell<u16>,
+}
+
+impl StepGen {
+    /// Create a new step generator at position 0 with one step pulse per [Self::step] call.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            position: Mutex::new(Cell::new(0)),
+            target: Mutex::new(Cell::new(0)),
+            interval: Mutex::new(Cell::new(1)),
+            countdown: IrqCtxCell::new(0),
+        }
+    }
+
+    /// Set the `target` position from `main()` context.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn set_target(&self, _: &MainCtx<'_>, target: i32) {
+        with_cs(|cs| self.target.borrow(cs).set(target));
+    }
+
+    /// Set the step rate from `main()` context.
+    ///
+    /// One step pulse is emitted every `interval` calls to [Self::step].
+    /// An `interval` of 0 is treated as 1.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn set_interval(&self, _: &MainCtx<'_>, interval: u16) {
+        with_cs(|cs| self.interval.borrow(cs).set(interval.max(1)));
+    }
+
+    /// Set the current `position` without emitting step pulses from `main()` context.
+    ///
+    /// This is useful after homing the motor.
+    /// The target position is set to `position` as well.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn set_position(&self, _: &MainCtx<'_>, position: i32) {
+        with_cs(|cs| {
+            self.position.borrow(cs).set(position);
+            self.target.borrow(cs).set(position);
+        });
+    }
+
+    /// Get the current position from `main()` context.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn position(&self, _: &MainCtx<'_>) -> i32 {
+        with_cs(|cs| self.position.borrow(cs).get())
+    }
+
+    /// Check whether the target position has been reached from `main()` context.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn is_done(&self, _: &MainCtx<'_>) -> bool {
+        with_cs(|cs| self.position.borrow(cs).get() == self.target.borrow(cs).get())
+    }
+
+    /// Advance the step generator by one tick from interrupt context.
+    ///
+    /// Returns the action that the interrupt service routine shall perform.
+    #[inline(always)]
+    pub fn step(&self, irq: &IrqCtx<'_>) -> StepAction {
+        let cs = irq.cs();
+        let countdown = self.countdown.get(irq);
+        if countdown > 0 {
+            self.countdown.set(irq, countdown - 1);
+            return StepAction::Wait;
+        }
+        let position = self.position.borrow(cs);
+        let pos = position.get();
+        let target = self.target.borrow(cs).get();
+        let action = if pos < target {
+            position.set(pos + 1);
+            StepAction::Forward
+        } else if pos > target {
+            position.set(pos - 1);
+            StepAction::Backward
+        } else {
+            return StepAction::Idle;
+        };
+        self.countdown
+            .set(irq, self.interval.borrow(cs).get().saturating_sub(1));
+        action
+    }
+}
+
+impl Default for StepGen {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_step_gen() {
+        let m = unsafe { MainCtx::new() };
+
+        let stepper = StepGen::new();
+        assert!(stepper.is_done(&m));
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert_eq!(stepper.step(&irq), StepAction::Idle);
+        }
+
+        stepper.set_target(&m, 3);
+        assert!(!stepper.is_done(&m));
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert_eq!(stepper.step(&irq), StepAction::Forward);
+            assert_eq!(stepper.step(&irq), StepAction::Forward);
+            assert_eq!(stepper.step(&irq), StepAction::Forward);
+            assert_eq!(stepper.step(&irq), StepAction::Idle);
+        }
+        assert!(stepper.is_done(&m));
+        assert_eq!(stepper.position(&m), 3);
+
+        stepper.set_interval(&m, 3);
+        stepper.set_target(&m, 1);
+        {
+            let irq = unsafe { IrqCtx::new() };
+            let mut actions = [StepAction::Idle; 8];
+            for action in &mut actions {
+                *action = stepper.step(&irq);
+            }
+            assert_eq!(
+                actions,
+                [
+                    StepAction::Backward,
+                    StepAction::Wait,
+                    StepAction::Wait,
+                    StepAction::Backward,
+                    StepAction::Wait,
+                    StepAction::Wait,
+                    StepAction::Idle,
+                    StepAction::Idle,
+                ]
+            );
+        }
+        assert!(stepper.is_done(&m));
+        assert_eq!(stepper.position(&m), 1);
+
+        stepper.set_position(&m, -100);
+        assert!(stepper.is_done(&m));
+        assert_eq!(stepper.position(&m), -100);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
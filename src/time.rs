@@ -0,0 +1,392 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Timestamps of interrupt events.
+
+use crate::{
+    Mutex,
+    cell::MainCtxCell,
+    context::{IrqCtx, MainCtx},
+    with_cs,
+};
+use core::cell::Cell;
+
+/// A free running clock.
+///
+/// This is usually implemented by reading a hardware timer counter,
+/// or by reading a tick counter that is incremented by a timer interrupt.
+///
+/// The clock value wraps around on overflow.
+pub trait Clock {
+    /// Get the current clock value.
+    fn now(&self) -> u32;
+}
+
+/// A slot for the timestamp of the most recent interrupt event.
+///
+/// The interrupt service routine captures the timestamp
+/// and the main loop takes it.
+/// The timestamp is protected by a critical section.
+pub struct TimestampSlot {
+    slot: Mutex<Cell<Option<u32>>>,
+}
+
+impl TimestampSlot {
+    /// Create a new empty timestamp slot.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            slot: Mutex::new(Cell::new(None)),
+        }
+    }
+
+    /// Capture the current time of `clock` into the slot from interrupt context.
+    ///
+    /// A previously captured timestamp that has not been taken is overwritten.
+    ///
+    /// Returns the captured timestamp.
+    #[inline(always)]
+    pub fn capture(&self, irq: &IrqCtx<'_>, clock: &impl Clock) -> u32 {
+        let timestamp = irq.timestamp(clock);
+        self.store(irq, timestamp);
+        timestamp
+    }
+
+    /// Store the `timestamp` into the slot from interrupt context.
+    ///
+    /// A previously stored timestamp that has not been taken is overwritten.
+    #[inline(always)]
+    pub fn store(&self, irq: &IrqCtx<'_>, timestamp: u32) {
+        self.slot.borrow(irq.cs()).set(Some(timestamp));
+    }
+
+    /// Take the timestamp out of the slot from `main()` context.
+    ///
+    /// Returns `None`, if no timestamp has been captured since the last call.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn take(&self, _: &MainCtx<'_>) -> Option<u32> {
+        with_cs(|cs| self.slot.borrow(cs).take())
+    }
+
+    /// Get the timestamp from `main()` context without removing it from the slot.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn peek(&self, _: &MainCtx<'_>) -> Option<u32> {
+        with_cs(|cs| self.slot.borrow(cs).get())
+    }
+}
+
+impl Default for TimestampSlot {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Measurement of the latency from an interrupt event to its processing in `main()` context.
+///
+/// The interrupt service routine marks the event with [Self::mark].
+/// The main loop calls [Self::measure] when it processes the event.
+/// Both sides must use the same clock.
+pub struct LatencyProbe {
+    slot: TimestampSlot,
+    max: MainCtxCell<u32>,
+}
+
+impl LatencyProbe {
+    /// Create a new latency probe without a marked event.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            slot: TimestampSlot::new(),
+            max: MainCtxCell::new(0),
+        }
+    }
+
+    /// Mark the event with the current time of `clock` from interrupt context.
+    ///
+    /// A previously marked event that has not been measured is overwritten.
+    #[inline(always)]
+    pub fn mark(&self, irq: &IrqCtx<'_>, clock: &impl Clock) {
+        self.slot.capture(irq, clock);
+    }
+
+    /// Measure the time elapsed since the marked event from `main()` context.
+    ///
+    /// Returns the latency in `clock` ticks
+    /// or `None`, if no event has been marked since the last call.
+    ///
+    /// This enters a critical section.
+    #[inline(always)]
+    pub fn measure(&self, m: &MainCtx<'_>, clock: &impl Clock) -> Option<u32> {
+        let marked = self.slot.take(m)?;
+        let latency = clock.now().wrapping_sub(marked);
+        self.max.set(m, self.max.get(m).max(latency));
+        Some(latency)
+    }
+
+    /// Get the maximum latency that has been measured so far.
+    #[inline(always)]
+    pub fn max(&self, m: &MainCtx<'_>) -> u32 {
+        self.max.get(m)
+    }
+}
+
+impl Default for LatencyProbe {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rate limiter for throttling actions in `main()` context.
+///
+/// This is useful to limit e.g. display updates to a maximum rate.
+/// The timestamps wrap around on overflow.
+pub struct RateLimiter {
+    last: MainCtxCell<Option<u32>>,
+}
+
+impl RateLimiter {
+    /// Create a new rate limiter that allows the first action immediately.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            last: MainCtxCell::new(None),
+        }
+    }
+
+    /// Check whether the action is allowed at the time `now`.
+    ///
+    /// Returns `true` and stores `now` as the time of the last action,
+    /// if at least `min_interval` ticks have passed since the last allowed action.
+    /// Returns `false` otherwise.
+    ///
+    /// The action is always allowed after construction or after [Self::reset].
+    #[inline(always)]
+    pub fn allow(&self, m: &MainCtx<'_>, now: u32, min_interval: u32) -> bool {
+        if let Some(last) = self.last.get(m)
+            && now.wrapping_sub(last) < min_interval
+        {
+            return false;
+        }
+        self.last.set(m, Some(now));
+        true
+    }
+
+    /// Forget the last action. The next action is allowed immediately.
+    #[inline(always)]
+    pub fn reset(&self, m: &MainCtx<'_>) {
+        self.last.set(m, None);
+    }
+}
+
+impl Default for RateLimiter {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Deadman heartbeat monitor for `N` subsystems in `main()` context.
+///
+/// Each subsystem has an id in the range `0..N` and reports that it is alive
+/// by calling [Self::beat] regularly.
+/// The main loop calls [Self::all_healthy] to decide whether to kick the hardware watchdog.
+/// If any subsystem stops beating, the watchdog is no longer kicked and resets the system.
+///
+/// The timestamps wrap around on overflow.
+pub struct Heartbeat<const N: usize> {
+    last: [MainCtxCell<Option<u32>>; N],
+    timeout: u32,
+}
+
+impl<const N: usize> Heartbeat<N> {
+    /// Create a new heartbeat monitor.
+    ///
+    /// A subsystem is healthy, if its last beat is less than `timeout` ticks old.
+    /// Subsystems that never did beat are unhealthy.
+    #[inline(always)]
+    pub const fn new(timeout: u32) -> Self {
+        Self {
+            last: [const { MainCtxCell::new(None) }; N],
+            timeout,
+        }
+    }
+
+    /// Report that subsystem `id` is alive at the time `now`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is not smaller than `N`.
+    #[inline(always)]
+    pub fn beat(&self, m: &MainCtx<'_>, id: usize, now: u32) {
+        self.last[id].set(m, Some(now));
+    }
+
+    /// Check whether subsystem `id` is healthy at the time `now`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is not smaller than `N`.
+    #[inline(always)]
+    pub fn is_healthy(&self, m: &MainCtx<'_>, id: usize, now: u32) -> bool {
+        self.last[id]
+            .get(m)
+            .is_some_and(|last| now.wrapping_sub(last) < self.timeout)
+    }
+
+    /// Check whether all subsystems are healthy at the time `now`.
+    ///
+    /// Only kick the watchdog, if this returns `true`.
+    #[inline(always)]
+    pub fn all_healthy(&self, m: &MainCtx<'_>, now: u32) -> bool {
+        (0..N).all(|id| self.is_healthy(m, id, now))
+    }
+
+    /// Forget all beats. All subsystems are unhealthy until they beat again.
+    #[inline(always)]
+    pub fn reset(&self, m: &MainCtx<'_>) {
+        for last in &self.last {
+            last.set(m, None);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+pub(crate) mod test {
+    use super::*;
+
+    /// A clock that returns the value of the cell.
+    pub(crate) struct MockClock(pub(crate) Cell<u32>);
+
+    impl Clock for MockClock {
+        fn now(&self) -> u32 {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn test_timestamp_slot() {
+        let m = unsafe { MainCtx::new() };
+
+        let clock = MockClock(Cell::new(100));
+        let slot = TimestampSlot::new();
+        assert_eq!(slot.take(&m), None);
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            assert_eq!(slot.capture(&irq, &clock), 100);
+        }
+        assert_eq!(slot.peek(&m), Some(100));
+        assert_eq!(slot.take(&m), Some(100));
+        assert_eq!(slot.take(&m), None);
+
+        clock.0.set(200);
+        {
+            let irq = unsafe { IrqCtx::new() };
+            slot.capture(&irq, &clock);
+            clock.0.set(250);
+            slot.capture(&irq, &clock);
+            assert_eq!(irq.elapsed_since(&clock, 200), 50);
+        }
+        assert_eq!(slot.take(&m), Some(250));
+    }
+
+    #[test]
+    fn test_latency_probe() {
+        let m = unsafe { MainCtx::new() };
+
+        let clock = MockClock(Cell::new(1000));
+        let probe = LatencyProbe::new();
+        assert_eq!(probe.measure(&m, &clock), None);
+
+        {
+            let irq = unsafe { IrqCtx::new() };
+            probe.mark(&irq, &clock);
+        }
+        clock.0.set(1030);
+        assert_eq!(probe.measure(&m, &clock), Some(30));
+        assert_eq!(probe.measure(&m, &clock), None);
+
+        clock.0.set(u32::MAX - 1);
+        {
+            let irq = unsafe { IrqCtx::new() };
+            probe.mark(&irq, &clock);
+        }
+        clock.0.set(8);
+        assert_eq!(probe.measure(&m, &clock), Some(10));
+        assert_eq!(probe.max(&m), 30);
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let m = unsafe { MainCtx::new() };
+
+        let limit = RateLimiter::new();
+        assert!(limit.allow(&m, 5, 20));
+        assert!(!limit.allow(&m, 6, 20));
+        assert!(!limit.allow(&m, 24, 20));
+        assert!(limit.allow(&m, 25, 20));
+        assert!(!limit.allow(&m, 30, 20));
+        assert!(limit.allow(&m, 100, 20));
+
+        limit.reset(&m);
+        assert!(limit.allow(&m, 101, 20));
+        assert!(limit.allow(&m, 101, 0));
+
+        // Wrap around.
+        assert!(limit.allow(&m, u32::MAX - 5, 20));
+        assert!(!limit.allow(&m, 3, 20));
+        assert!(limit.allow(&m, 14, 20));
+    }
+
+    #[test]
+    fn test_heartbeat() {
+        let m = unsafe { MainCtx::new() };
+
+        let hb: Heartbeat<3> = Heartbeat::new(100);
+        assert!(!hb.all_healthy(&m, 0));
+
+        hb.beat(&m, 0, 10);
+        hb.beat(&m, 1, 20);
+        assert!(!hb.all_healthy(&m, 30));
+        hb.beat(&m, 2, 30);
+        assert!(hb.all_healthy(&m, 30));
+        assert!(hb.all_healthy(&m, 109));
+
+        // Subsystem 0 misses its beat.
+        hb.beat(&m, 1, 100);
+        hb.beat(&m, 2, 100);
+        assert!(!hb.all_healthy(&m, 110));
+        assert!(!hb.is_healthy(&m, 0, 110));
+        assert!(hb.is_healthy(&m, 1, 110));
+
+        hb.beat(&m, 0, 110);
+        assert!(hb.all_healthy(&m, 110));
+
+        // Wrap around.
+        hb.beat(&m, 0, u32::MAX - 10);
+        hb.beat(&m, 1, u32::MAX - 10);
+        hb.beat(&m, 2, u32::MAX - 10);
+        assert!(hb.all_healthy(&m, 50));
+        assert!(!hb.all_healthy(&m, 89));
+
+        hb.reset(&m);
+        assert!(!hb.all_healthy(&m, u32::MAX));
+    }
+
+    #[test]
+    fn test_elapsed_since_wrapping() {
+        let clock = MockClock(Cell::new(5));
+        let irq = unsafe { IrqCtx::new() };
+        assert_eq!(irq.elapsed_since(&clock, u32::MAX - 4), 10);
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
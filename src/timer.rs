@@ -0,0 +1,178 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Software timers in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+
+/// Set of timers that fired in one [TimerWheel::tick].
+///
+/// Bit `n` is set, if timer `n` fired.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimerEvents(pub u32);
+
+impl TimerEvents {
+    /// Returns `true`, if timer `id` fired.
+    #[inline(always)]
+    pub fn fired(&self, id: usize) -> bool {
+        id < 32 && self.0 & (1 << id) != 0
+    }
+
+    /// Returns `true`, if no timer fired.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterate over the ids of all timers that fired.
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = usize> {
+        let bits = self.0;
+        (0..32).filter(move |id| bits & (1 << id) != 0)
+    }
+}
+
+/// Up to 32 one-shot software timers that can only be accessed from `main()` context.
+///
+/// The main loop calls [Self::tick] once per timer period,
+/// e.g. after a timer interrupt has signalled a new tick.
+/// Each armed timer counts down and fires when it reaches zero.
+/// A fired timer is disarmed.
+pub struct TimerWheel<const N: usize> {
+    remaining: MainCtxCell<[u16; N]>,
+}
+
+impl<const N: usize> TimerWheel<N> {
+    /// Create a new timer wheel with all timers disarmed.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        const { assert!(N <= 32) };
+        Self {
+            remaining: MainCtxCell::new([0; N]),
+        }
+    }
+
+    /// Arm the timer `id` to fire after `ticks` calls to [Self::tick].
+    ///
+    /// An already armed timer is restarted.
+    /// Arming with zero `ticks` disarms the timer.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is out of bounds.
+    #[inline(always)]
+    pub fn arm(&self, m: &MainCtx<'_>, id: usize, ticks: u16) {
+        self.remaining.set_index(m, id, ticks);
+    }
+
+    /// Disarm the timer `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is out of bounds.
+    #[inline(always)]
+    pub fn disarm(&self, m: &MainCtx<'_>, id: usize) {
+        self.arm(m, id, 0);
+    }
+
+    /// Get the remaining ticks of timer `id`.
+    ///
+    /// Returns 0, if the timer is not armed.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is out of bounds.
+    #[inline(always)]
+    pub fn remaining(&self, m: &MainCtx<'_>, id: usize) -> u16 {
+        self.remaining.index(m, id)
+    }
+
+    /// Returns `true`, if timer `id` is armed.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `id` is out of bounds.
+    #[inline(always)]
+    pub fn is_armed(&self, m: &MainCtx<'_>, id: usize) -> bool {
+        self.remaining(m, id) != 0
+    }
+
+    /// Advance all armed timers by one tick.
+    ///
+    /// Returns the timers that fired.
+    #[inline(always)]
+    pub fn tick(&self, m: &MainCtx<'_>) -> TimerEvents {
+        let mut fired = 0;
+        let mut id = 0;
+        self.remaining.with_each_mut(m, |remaining| {
+            if *remaining != 0 {
+                *remaining -= 1;
+                if *remaining == 0 {
+                    fired |= 1 << id;
+                }
+            }
+            id += 1;
+        });
+        TimerEvents(fired)
+    }
+}
+
+impl<const N: usize> Default for TimerWheel<N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_timer_wheel() {
+        let m = unsafe { MainCtx::new() };
+
+        let timers: TimerWheel<4> = TimerWheel::new();
+        assert!(timers.tick(&m).is_empty());
+
+        timers.arm(&m, 0, 3);
+        timers.arm(&m, 2, 1);
+        timers.arm(&m, 3, 3);
+        assert!(timers.is_armed(&m, 0));
+        assert!(!timers.is_armed(&m, 1));
+
+        let events = timers.tick(&m);
+        assert_eq!(events, TimerEvents(0b0100));
+        assert!(events.fired(2));
+        assert!(!events.fired(0));
+        assert!(!timers.is_armed(&m, 2));
+        assert_eq!(timers.remaining(&m, 0), 2);
+
+        timers.disarm(&m, 3);
+        assert!(timers.tick(&m).is_empty());
+        let events = timers.tick(&m);
+        assert_eq!(events.iter().collect::<Vec<_>>(), [0]);
+        assert!(timers.tick(&m).is_empty());
+
+        // Restart an armed timer.
+        timers.arm(&m, 1, 2);
+        assert!(timers.tick(&m).is_empty());
+        timers.arm(&m, 1, 2);
+        assert!(timers.tick(&m).is_empty());
+        assert!(timers.tick(&m).fired(1));
+    }
+
+    #[test]
+    fn test_timer_events() {
+        let events = TimerEvents(0x8000_0003);
+        assert_eq!(events.iter().collect::<Vec<_>>(), [0, 1, 31]);
+        assert!(events.fired(31));
+        assert!(!events.fired(32));
+        assert!(TimerEvents::default().is_empty());
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,117 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Access tracing of [MainCtxCell](crate::MainCtxCell)s for host tests.
+//!
+//! This module is only available with the `access-trace` feature on non-AVR targets.
+//! On AVR the trace hooks compile to nothing.
+//!
+//! While tracing is enabled on a thread with [start],
+//! every access to a `MainCtxCell` on that thread is recorded.
+//! [stop] returns the recorded accesses in the order they happened.
+//! That way host tests of firmware logic can assert the expected access sequence.
+
+use std::{cell::RefCell, vec::Vec};
+
+/// Type of a traced cell access.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessOp {
+    /// The cell data has been read.
+    Read,
+    /// The cell data has been written.
+    Write,
+    /// The cell data has been replaced and the old data has been returned.
+    Replace,
+    /// The cell data has been modified in place.
+    Modify,
+}
+
+/// A traced cell access.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Access {
+    /// The address of the accessed cell.
+    pub addr: usize,
+    /// The type of the access.
+    pub op: AccessOp,
+}
+
+impl Access {
+    /// Create a new access record for the `cell`.
+    #[inline]
+    pub fn new<T: ?Sized>(cell: &T, op: AccessOp) -> Self {
+        Self {
+            addr: cell as *const T as *const () as usize,
+            op,
+        }
+    }
+}
+
+std::thread_local! {
+    static LOG: RefCell<Option<Vec<Access>>> = const { RefCell::new(None) };
+}
+
+/// Start recording cell accesses on the current thread.
+///
+/// Previously recorded accesses are discarded.
+pub fn start() {
+    LOG.with_borrow_mut(|log| *log = Some(Vec::new()));
+}
+
+/// Stop recording cell accesses on the current thread
+/// and return all accesses that have been recorded since [start].
+pub fn stop() -> Vec<Access> {
+    LOG.with_borrow_mut(|log| log.take()).unwrap_or_default()
+}
+
+#[inline]
+pub(crate) fn record<T: ?Sized>(cell: &T, op: AccessOp) {
+    LOG.with_borrow_mut(|log| {
+        if let Some(log) = log {
+            log.push(Access::new(cell, op));
+        }
+    });
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use crate::{MainCtx, MainCtxCell};
+
+    #[test]
+    fn test_access_trace() {
+        let m = unsafe { MainCtx::new() };
+
+        let a: MainCtxCell<u16> = MainCtxCell::new(1);
+        let b: MainCtxCell<[u8; 2]> = MainCtxCell::new([2, 3]);
+
+        // Not recorded.
+        a.set(&m, 4);
+
+        start();
+        let value = a.get(&m);
+        b.set(&m, [value as u8, 5]);
+        a.replace(&m, 6);
+        b.with_each_mut(&m, |e| *e += 1);
+        let _ = b.as_ref(&m);
+        let trace = stop();
+
+        // Not recorded.
+        a.set(&m, 7);
+        assert!(stop().is_empty());
+
+        assert_eq!(
+            trace,
+            [
+                Access::new(&a, AccessOp::Read),
+                Access::new(&b, AccessOp::Write),
+                Access::new(&a, AccessOp::Replace),
+                Access::new(&b, AccessOp::Modify),
+                Access::new(&b, AccessOp::Read),
+            ]
+        );
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,104 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+//! Software watchpoints for debugging in `main()` context.
+
+use crate::{cell::MainCtxCell, context::MainCtx};
+#[cfg(not(debug_assertions))]
+use core::marker::PhantomData;
+
+/// Software watchpoint on a [MainCtxCell] for debug builds.
+///
+/// Call [Self::watch] once per main loop iteration.
+/// It compares the cell against the value that has been seen in the previous call
+/// and calls the `on_change` callback, if the value has changed.
+/// This is useful to find unexpected state changes without a hardware debugger.
+///
+/// In release builds the watchpoint compiles to nothing
+/// and the callback is never called.
+pub struct Watchpoint<T> {
+    #[cfg(debug_assertions)]
+    last: MainCtxCell<Option<T>>,
+    #[cfg(not(debug_assertions))]
+    _last: PhantomData<T>,
+}
+
+impl<T> Watchpoint<T> {
+    /// Create a new watchpoint.
+    ///
+    /// The first call to [Self::watch] only records the current value.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            last: MainCtxCell::new(None),
+            #[cfg(not(debug_assertions))]
+            _last: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy + PartialEq> Watchpoint<T> {
+    /// Check the `cell` for a change since the previous call.
+    ///
+    /// If the value has changed, `on_change` is called with the old and the new value.
+    ///
+    /// This does nothing in release builds.
+    #[inline(always)]
+    pub fn watch(&self, m: &MainCtx<'_>, cell: &MainCtxCell<T>, on_change: impl FnOnce(T, T)) {
+        #[cfg(debug_assertions)]
+        {
+            let new = cell.get(m);
+            if let Some(old) = self.last.replace(m, Some(new))
+                && old != new
+            {
+                on_change(old, new);
+            }
+        }
+        #[cfg(not(debug_assertions))]
+        let _ = (m, cell, on_change);
+    }
+}
+
+impl<T> Default for Watchpoint<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::undocumented_unsafe_blocks)]
+mod test {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_watchpoint() {
+        let m = unsafe { MainCtx::new() };
+
+        let cell = MainCtxCell::new(1_u8);
+        let wp = Watchpoint::new();
+        let mut changes = Vec::new();
+
+        wp.watch(&m, &cell, |old, new| changes.push((old, new)));
+        wp.watch(&m, &cell, |old, new| changes.push((old, new)));
+        cell.set(&m, 2);
+        wp.watch(&m, &cell, |old, new| changes.push((old, new)));
+        wp.watch(&m, &cell, |old, new| changes.push((old, new)));
+        cell.set(&m, 3);
+        cell.set(&m, 2);
+        wp.watch(&m, &cell, |old, new| changes.push((old, new)));
+        cell.set(&m, 4);
+        wp.watch(&m, &cell, |old, new| changes.push((old, new)));
+
+        if cfg!(debug_assertions) {
+            assert_eq!(changes, [(1, 2), (2, 4)]);
+        } else {
+            assert!(changes.is_empty());
+        }
+    }
+}
+
+// vim: ts=4 sw=4 expandtab
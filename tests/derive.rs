@@ -0,0 +1,13 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+#![cfg(feature = "derive")]
+
+#[test]
+fn test_derive_main_ctx_access() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_main_ctx_access.rs");
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,96 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+#![cfg(feature = "std-test")]
+#![allow(clippy::undocumented_unsafe_blocks)]
+
+use avr_context::{MainCtx, MainCtxCell, flag::IrqFlag, sim, spsc::SpscQueue};
+use std::time::{Duration, Instant};
+
+const SAMPLES: usize = 2000;
+
+static QUEUE: SpscQueue<u32, 16> = SpscQueue::new();
+static DONE: IrqFlag = IrqFlag::new();
+
+static RECEIVED: MainCtxCell<usize> = MainCtxCell::new(0);
+static CHECKSUM: MainCtxCell<u64> = MainCtxCell::new(0);
+
+fn sample(i: usize) -> u32 {
+    (i as u32).wrapping_mul(0x9E37_79B9)
+}
+
+#[test]
+fn test_spsc_and_flag_under_pseudo_irq() {
+    let m = unsafe { MainCtx::new() };
+
+    let isr = std::thread::spawn(|| {
+        for i in 0..SAMPLES {
+            // Retry until the main loop made room. That simulates a
+            // producer that keeps its data until it can be delivered.
+            // Wait outside of the pseudo ISR, so that the global
+            // critical section is only held for the queue access.
+            let mut value = sample(i);
+            while let Err(v) = sim::run_isr(|irq| QUEUE.enqueue(irq, value)) {
+                value = v;
+                std::thread::yield_now();
+            }
+            if i == SAMPLES - 1 {
+                sim::run_isr(|irq| DONE.set(irq));
+            }
+            std::thread::sleep(Duration::from_micros(20));
+        }
+    });
+
+    let start = Instant::now();
+    let mut done = false;
+    loop {
+        // Main loop. Receive and check all samples in order.
+        while let Some(value) = QUEUE.dequeue(&m) {
+            let received = RECEIVED.get(&m);
+            assert_eq!(value, sample(received), "corrupted or lost sample");
+            RECEIVED.set(&m, received + 1);
+            CHECKSUM.set(&m, CHECKSUM.get(&m) + value as u64);
+        }
+        if done && QUEUE.is_empty(&m) {
+            break;
+        }
+        done |= DONE.take(&m);
+        assert!(start.elapsed() < Duration::from_secs(60), "timeout");
+    }
+    isr.join().unwrap();
+
+    assert_eq!(RECEIVED.get(&m), SAMPLES);
+    let expected: u64 = (0..SAMPLES).map(|i| sample(i) as u64).sum();
+    assert_eq!(CHECKSUM.get(&m), expected);
+    assert!(!DONE.take(&m));
+}
+
+#[test]
+fn test_run_isr_serializes_with_critical_sections() {
+    let counter = std::sync::Arc::new(avr_context::Mutex::new(core::cell::Cell::new(0_u32)));
+
+    let isr = {
+        let counter = counter.clone();
+        sim::spawn_isr(Duration::ZERO, 1000, move |irq, _| {
+            let c = counter.borrow(irq.cs());
+            // Non-atomic read-modify-write. Only correct if serialized.
+            let v = c.get();
+            std::thread::yield_now();
+            c.set(v + 1);
+        })
+    };
+    for _ in 0..1000 {
+        avr_context::with_cs(|cs| {
+            let c = counter.borrow(cs);
+            let v = c.get();
+            std::thread::yield_now();
+            c.set(v + 1);
+        });
+    }
+    isr.join().unwrap();
+
+    assert_eq!(avr_context::with_cs(|cs| counter.borrow(cs).get()), 2000);
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,44 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+#![cfg(all(feature = "panic-dump", feature = "std-test"))]
+#![allow(clippy::undocumented_unsafe_blocks)]
+
+use avr_context::{MainCtx, MainCtxCell, panicdump::PanicDump};
+use std::sync::Mutex;
+
+static SPEED: MainCtxCell<u16> = MainCtxCell::new(0);
+static STATE: MainCtxCell<(u8, bool)> = MainCtxCell::new((0, false));
+static DUMP: PanicDump<4> = PanicDump::new();
+static OUTPUT: Mutex<String> = Mutex::new(String::new());
+
+fn main_loop(m: &MainCtx<'_>) {
+    SPEED.set(m, 800);
+    STATE.set(m, (3, true));
+    panic!("motor stalled");
+}
+
+#[test]
+fn test_panic_hook_dumps_cells() {
+    let m = unsafe { MainCtx::new() };
+    DUMP.register(&m, 0, "speed", &SPEED);
+    DUMP.register(&m, 1, "state", &STATE);
+
+    std::panic::set_hook(Box::new(|info| {
+        let mut out = OUTPUT.lock().unwrap();
+        out.push_str(&format!("{}\n", info.payload_as_str().unwrap_or("")));
+        // The main loop does not run anymore.
+        unsafe { DUMP.dump(&mut *out) }.unwrap();
+    }));
+    let res = std::panic::catch_unwind(|| main_loop(&m));
+    let _ = std::panic::take_hook();
+    assert!(res.is_err());
+
+    assert_eq!(
+        *OUTPUT.lock().unwrap(),
+        "motor stalled\nspeed=800\nstate=(3, true)\n"
+    );
+}
+
+// vim: ts=4 sw=4 expandtab
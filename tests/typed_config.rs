@@ -0,0 +1,11 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+#[test]
+fn test_typed_config() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/typed_config.rs");
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,42 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+use avr_context::{MainCtx, MainCtxAccess, MainCtxCell};
+
+#[derive(MainCtxAccess)]
+pub struct DeviceState {
+    pub speed: MainCtxCell<u16>,
+    pub mode: avr_context::cell::MainCtxCell<[u8; 2]>,
+    enabled: MainCtxCell<bool>,
+    pub name: &'static str,
+}
+
+#[derive(MainCtxAccess)]
+pub struct Generic<T: Copy> {
+    pub value: MainCtxCell<T>,
+}
+
+impl DeviceState {
+    pub fn toggle(&self, m: &MainCtx<'_>) -> bool {
+        let enabled = !self.enabled(m);
+        self.set_enabled(m, enabled);
+        enabled
+    }
+}
+
+pub fn update(state: &DeviceState, generic: &Generic<u32>, m: &MainCtx<'_>) -> u32 {
+    state.set_speed(m, state.speed(m) + 1);
+    state.set_mode(m, [1, 2]);
+    state.toggle(m);
+    generic.set_value(m, u32::from(state.mode(m)[1]));
+    generic.value(m)
+}
+
+fn main() {
+    // Constructing a `MainCtx` is not possible outside of unit tests on the host.
+    // Only check that the generated accessors compile.
+    let _ = update;
+}
+
+// vim: ts=4 sw=4 expandtab
@@ -0,0 +1,53 @@
+// -*- coding: utf-8 -*-
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+// Copyright (C) 2025 - 2026 Michael Büsch <m@bues.ch>
+
+#![deny(missing_docs)]
+
+//! Configuration test.
+
+use avr_context::{InitCtx, MainCtx, typed_config};
+
+typed_config! {
+    /// Firmware configuration.
+    #[derive(Clone, Debug, Default)]
+    pub struct Config {
+        /// Baud rate.
+        pub baud: u32,
+        /// Bus address.
+        pub address: u8,
+        secret: [u8; 4],
+    }
+    static CONFIG;
+}
+
+/// Initialize the configuration.
+pub fn init(c: &InitCtx<'_>) -> u8 {
+    let config = Config::init(
+        c,
+        Config {
+            baud: 9600,
+            address: 0x12,
+            secret: [1, 2, 3, 4],
+        },
+    );
+    config.map_or(0, |c| c.address)
+}
+
+/// Read the configuration.
+pub fn read(m: &MainCtx<'_>) -> (u32, u8, u8, Config) {
+    (
+        *Config::baud(m),
+        *Config::address(m),
+        Config::secret(m)[3],
+        Config::get(m).clone(),
+    )
+}
+
+fn main() {
+    // Constructing a `MainCtx` is not possible outside of unit tests on the host.
+    // Only check that the generated accessors compile.
+    let _ = (init, read);
+}
+
+// vim: ts=4 sw=4 expandtab